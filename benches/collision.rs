@@ -0,0 +1,94 @@
+//! Compares the asteroid broadphase against checking every bullet against every asteroid.
+//!
+//! Run with `cargo bench --no-default-features --bench collision`.
+
+use std::ops::Range;
+
+use asteroids::{
+    arena::{Arena, EdgeMode},
+    collision::{GRID_CELL_SIZE, SpatialGrid},
+};
+use bevy::prelude::*;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The number of bullets that look for nearby asteroids every tick.
+const BULLETS: usize = 100;
+/// The radius the bullets use to look for asteroids.
+const BULLET_RADIUS: f32 = 3.5;
+
+/// Scatters `count` circles with radii in the given range across the arena.
+fn scatter(
+    rng: &mut ChaCha8Rng,
+    arena: &Arena,
+    count: usize,
+    radii: Range<f32>,
+) -> Vec<(Vec2, f32)> {
+    let half_size = arena.half_size();
+
+    (0..count)
+        .map(|_| {
+            let position = Vec2::new(
+                rng.random_range(-half_size.x..half_size.x),
+                rng.random_range(-half_size.y..half_size.y),
+            );
+            (position, rng.random_range(radii.clone()))
+        })
+        .collect()
+}
+
+fn asteroid_collisions(c: &mut Criterion) {
+    for edge_mode in [EdgeMode::Bounded, EdgeMode::Wrap] {
+        let arena = Arena::default().with_edge_mode(edge_mode);
+        let mut group = c.benchmark_group(format!("asteroid_collisions/{edge_mode:?}"));
+
+        for count in [100, 1_000, 5_000, 10_000] {
+            let mut rng = ChaCha8Rng::seed_from_u64(count as u64);
+            let asteroids = scatter(&mut rng, &arena, count, 8.0..60.0);
+            let bullets = scatter(&mut rng, &arena, BULLETS, 0.0..BULLET_RADIUS);
+
+            group.throughput(Throughput::Elements(count as u64));
+
+            // Rebuild the grid and look up the asteroids near every bullet, like a tick does.
+            let mut grid = SpatialGrid::new(GRID_CELL_SIZE);
+            let mut with_grid = || {
+                grid.clear(&arena);
+                for (index, &(position, radius)) in asteroids.iter().enumerate() {
+                    grid.insert(Entity::from_raw(index as u32), position, radius);
+                }
+
+                bullets
+                    .iter()
+                    .map(|&(position, _)| grid.query(position, BULLET_RADIUS).count())
+                    .sum::<usize>()
+            };
+
+            // Check every bullet against every asteroid.
+            let all_pairs = || {
+                bullets
+                    .iter()
+                    .map(|&(bullet, _)| {
+                        asteroids
+                            .iter()
+                            .filter(|&&(asteroid, radius)| {
+                                let offset = arena.nearest_copy_offset(bullet, asteroid);
+                                bullet.distance(asteroid + offset) < BULLET_RADIUS + radius
+                            })
+                            .count()
+                    })
+                    .sum::<usize>()
+            };
+
+            assert_eq!(with_grid(), all_pairs(), "the grid missed a collision");
+
+            group.bench_function(BenchmarkId::new("grid", count), |b| b.iter(&mut with_grid));
+            group.bench_function(BenchmarkId::new("all_pairs", count), |b| b.iter(all_pairs));
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, asteroid_collisions);
+criterion_main!(benches);
@@ -0,0 +1,170 @@
+//! Data for the logical arena the game is played in.
+
+use bevy::prelude::*;
+
+/// What happens to objects that reach the edge of the arena.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EdgeMode {
+    /// The ship is held inside the arena, and everything else is removed once it leaves.
+    #[default]
+    Bounded,
+    /// Everything re-enters from the opposite edge, like the classic arcade game.
+    Wrap,
+}
+
+/// The logical size of the playfield, centred on the origin.
+///
+/// Gameplay systems read the arena instead of the primary window so the game can be
+/// simulated without one. When a window exists, the arena is kept in sync with it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    /// The width and height of the arena in world units.
+    pub size: Vec2,
+    /// What happens to objects that reach the edge of the arena.
+    pub edge_mode: EdgeMode,
+}
+
+impl Arena {
+    /// Creates a new bounded arena with the given width and height.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            size: Vec2::new(width, height),
+            edge_mode: EdgeMode::default(),
+        }
+    }
+
+    /// Returns the arena with the given `EdgeMode`.
+    pub fn with_edge_mode(mut self, edge_mode: EdgeMode) -> Self {
+        self.edge_mode = edge_mode;
+        self
+    }
+
+    /// Returns half of the arena's size, i.e. the distance from the centre to each edge.
+    pub fn half_size(&self) -> Vec2 {
+        self.size / 2.0
+    }
+
+    /// Returns the width of the arena.
+    pub fn width(&self) -> f32 {
+        self.size.x
+    }
+
+    /// Returns the height of the arena.
+    pub fn height(&self) -> f32 {
+        self.size.y
+    }
+
+    /// Returns `true` if objects wrap around the edges of the arena.
+    pub fn wraps(&self) -> bool {
+        self.edge_mode == EdgeMode::Wrap
+    }
+
+    /// Returns `true` if the arena has an area to wrap points into.
+    ///
+    /// A minimized window can have no area, and wrapping into it would give NaN positions.
+    fn can_wrap(&self) -> bool {
+        self.wraps() && self.size.cmpgt(Vec2::ZERO).all()
+    }
+
+    /// Returns how far a point has to move to wrap back inside the arena.
+    ///
+    /// This is zero for points inside the arena, or if the arena doesn't wrap.
+    pub fn wrap_offset(&self, point: Vec2) -> Vec2 {
+        if !self.can_wrap() {
+            return Vec2::ZERO;
+        }
+
+        let half_size = self.half_size();
+
+        // Shift the point so the arena starts at zero, wrap it, and shift it back.
+        let wrapped = (point + half_size).rem_euclid(self.size) - half_size;
+
+        wrapped - point
+    }
+
+    /// Returns the offset that moves `to` onto its copy nearest to `from`.
+    ///
+    /// Objects that straddle an edge of a wrapping arena are partly drawn on the opposite
+    /// side, so collisions have to be checked against the closest copy of each object.
+    /// This is zero if the arena doesn't wrap.
+    pub fn nearest_copy_offset(&self, from: Vec2, to: Vec2) -> Vec2 {
+        if !self.can_wrap() {
+            return Vec2::ZERO;
+        }
+
+        let delta = to - from;
+        let nearest = delta - (delta / self.size).round() * self.size;
+
+        nearest - delta
+    }
+}
+
+impl Default for Arena {
+    /// Matches the size of Bevy's default window.
+    fn default() -> Self {
+        Self::new(1280.0, 720.0)
+    }
+}
+
+/// Draws copies of an entity on the opposite edges of a wrapping arena while it
+/// straddles them.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WrapGhosts {
+    /// The distance from the entity's centre to the furthest point of its shape.
+    pub radius: f32,
+}
+
+/// One of the copies drawn for a [`WrapGhosts`] entity.
+///
+/// The ghost sits one arena width and/or height away from its parent, on the side
+/// given by `axes`: `(1, 0)` is the horizontal copy, `(0, 1)` the vertical one and
+/// `(1, 1)` the diagonal one for corners.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WrapGhost {
+    /// Which axes the ghost is offset along.
+    pub axes: Vec2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_points_back_inside() {
+        let arena = Arena::new(100.0, 50.0).with_edge_mode(EdgeMode::Wrap);
+
+        assert_eq!(arena.wrap_offset(Vec2::new(10.0, 5.0)), Vec2::ZERO);
+        assert_eq!(
+            arena.wrap_offset(Vec2::new(60.0, 0.0)),
+            Vec2::new(-100.0, 0.0)
+        );
+        assert_eq!(
+            arena.wrap_offset(Vec2::new(0.0, -30.0)),
+            Vec2::new(0.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn finds_the_nearest_copy_across_an_edge() {
+        let arena = Arena::new(100.0, 50.0).with_edge_mode(EdgeMode::Wrap);
+
+        let offset = arena.nearest_copy_offset(Vec2::new(45.0, 0.0), Vec2::new(-45.0, 0.0));
+        assert_eq!(offset, Vec2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn an_empty_arena_does_not_wrap() {
+        for size in [Vec2::ZERO, Vec2::new(100.0, 0.0)] {
+            let arena = Arena {
+                size,
+                edge_mode: EdgeMode::Wrap,
+            };
+
+            assert_eq!(arena.wrap_offset(Vec2::new(10.0, 20.0)), Vec2::ZERO);
+            assert_eq!(
+                arena.nearest_copy_offset(Vec2::ZERO, Vec2::new(10.0, 20.0)),
+                Vec2::ZERO
+            );
+        }
+    }
+}
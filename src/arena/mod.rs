@@ -0,0 +1,8 @@
+//! # Arena
+//!
+//! This module contains the logical playfield that the game is simulated in.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
@@ -0,0 +1,124 @@
+//! Systems for keeping the arena in sync with the game window, and drawing objects
+//! that wrap around its edges.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::{Arena, WrapGhost, WrapGhosts};
+use crate::interpolation::TransformInterpolation;
+
+/// Wraps a transform back inside a wrapping arena, if it has left it.
+///
+/// The interpolation is moved along with it, so the entity doesn't visibly slide
+/// across the whole arena between the two ticks.
+///
+/// # Arguments
+/// * `arena`: The `Arena` to wrap the transform inside of.
+/// * `transform`: The `Transform` to wrap.
+/// * `interpolation`: The entity's `TransformInterpolation`, if it has one.
+pub fn wrap_transform(
+    arena: &Arena,
+    transform: &mut Transform,
+    interpolation: Option<Mut<TransformInterpolation>>,
+) {
+    let offset = arena.wrap_offset(transform.translation.truncate());
+
+    if offset == Vec2::ZERO {
+        return;
+    }
+
+    transform.translation += offset.extend(0.0);
+
+    if let Some(previous) = interpolation.and_then(|i| i.into_inner().previous.as_mut()) {
+        previous.translation += offset.extend(0.0);
+    }
+}
+
+/// Resizes the `Arena` to match the primary window, if there is one.
+///
+/// # Arguments
+/// * `arena`: The `Arena` resource to resize.
+/// * `window`: A query that retrieves the primary window to get its size.
+pub fn sync_arena_to_window(mut arena: ResMut<Arena>, window: Query<&Window, With<PrimaryWindow>>) {
+    let Ok(window) = window.single() else {
+        return;
+    };
+
+    let size = Vec2::new(window.width(), window.height());
+
+    // Keep playing in the last size while the window is minimized.
+    if size.cmple(Vec2::ZERO).any() {
+        return;
+    }
+
+    // Only touch the resource when the size changes so change detection stays useful.
+    if arena.size != size {
+        arena.size = size;
+    }
+}
+
+/// Spawns the ghost copies of new `WrapGhosts` entities as hidden children.
+///
+/// # Arguments
+/// * `commands`: The `Commands` resource to spawn the ghosts.
+/// * `query`: A query that retrieves every new `WrapGhosts` entity with its mesh and material.
+pub fn spawn_wrap_ghosts(
+    mut commands: Commands,
+    query: Query<(Entity, &Mesh2d, &MeshMaterial2d<ColorMaterial>), Added<WrapGhosts>>,
+) {
+    for (entity, mesh, material) in query.iter() {
+        commands.entity(entity).with_children(|parent| {
+            for axes in [Vec2::X, Vec2::Y, Vec2::ONE] {
+                parent.spawn((
+                    WrapGhost { axes },
+                    mesh.clone(),
+                    material.clone(),
+                    Transform::default(),
+                    Visibility::Hidden,
+                ));
+            }
+        });
+    }
+}
+
+/// Moves each ghost to the opposite side of the arena from its parent, and only shows
+/// it while the parent straddles that edge.
+///
+/// # Arguments
+/// * `parents`: A query that retrieves every `WrapGhosts` entity with its `Transform`.
+/// * `ghosts`: A query that retrieves every `WrapGhost` with its `Transform` and `Visibility`.
+/// * `arena`: The `Arena` resource to get the size of the playfield.
+pub fn update_wrap_ghosts(
+    parents: Query<(&Transform, &WrapGhosts, &Children)>,
+    mut ghosts: Query<(&WrapGhost, &mut Transform, &mut Visibility), Without<WrapGhosts>>,
+    arena: Res<Arena>,
+) {
+    let half_size = arena.half_size();
+
+    for (parent_transform, wrap_ghosts, children) in parents.iter() {
+        let position = parent_transform.translation.truncate();
+
+        // Which edges the parent straddles, and which way to go to reach the opposite one.
+        let straddles = (position.abs() + wrap_ghosts.radius).cmpgt(half_size);
+        let direction = -position.signum();
+
+        let mut ghost_iter = ghosts.iter_many_mut(children);
+        while let Some((ghost, mut transform, mut visibility)) = ghost_iter.fetch_next() {
+            let visible = arena.wraps()
+                && (ghost.axes.x == 0.0 || straddles.x)
+                && (ghost.axes.y == 0.0 || straddles.y);
+
+            *visibility = if visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+
+            if visible {
+                // The offset is in world space, but children are placed in their parent's space.
+                let offset = (ghost.axes * direction * arena.size).extend(0.0);
+                transform.translation =
+                    parent_transform.rotation.inverse() * offset / parent_transform.scale;
+            }
+        }
+    }
+}
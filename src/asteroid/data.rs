@@ -3,6 +3,11 @@
 use bevy::{asset::RenderAssetUsages, prelude::*, render::mesh::PrimitiveTopology};
 use rand::Rng;
 
+use crate::{
+    arena::WrapGhosts, collision::CollisionPolygon, interpolation::TransformInterpolation,
+    physics::Velocity,
+};
+
 /// Parameters that define the `min_diameter, max_diameter, number_of_points` for small asteroids.
 pub const SMALL_PARAMETERS: AsteroidParameters = AsteroidParameters {
     min_diameter: 8.0,
@@ -24,6 +29,16 @@ pub const LARGE_PARAMETERS: AsteroidParameters = AsteroidParameters {
     number_of_points: 30,
 };
 
+/// How often the asteroid spawner gets a chance to spawn new asteroids.
+#[derive(Resource, Debug)]
+pub struct AsteroidSpawnTimer(pub Timer);
+
+impl Default for AsteroidSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.5, TimerMode::Repeating))
+    }
+}
+
 /// Parameters for asteroid generation.
 #[derive(Debug, Clone, Copy)]
 pub struct AsteroidParameters {
@@ -59,21 +74,29 @@ impl AsteroidSize {
     }
 }
 
-/// Represents an `Asteroid` in the game.
+/// Represents an `Asteroid` in the game. It drifts with its `Velocity`.
 #[derive(Component, Debug)]
 pub struct Asteroid {
     /// The `size` of the asteroid. See [`AsteroidSize`].
     pub size: AsteroidSize,
-    /// The `direction` of the asteroid's movement.
-    /// This is a vector that indicates the direction and speed of the asteroid.
-    pub direction: Vec2,
 }
 
 impl Asteroid {
+    /// Spawns a new asteroid with a randomly generated shape.
+    ///
+    /// # Arguments
+    /// * `size`: The `AsteroidSize` of the new asteroid.
+    /// * `location`: Where to spawn the asteroid.
+    /// * `velocity`: The direction and speed of the asteroid's movement.
+    /// * `rng`: The random number generator used to generate the asteroid's shape.
+    /// * `commands`: The `Commands` resource to spawn the asteroid entity.
+    /// * `meshes`: The `Assets<Mesh>` resource to create the asteroid mesh.
+    /// * `materials`: The `Assets<ColorMaterial>` resource to create the asteroid material.
     pub fn spawn_new(
         size: AsteroidSize,
         location: Vec3,
-        direction: Vec2,
+        velocity: Vec2,
+        rng: &mut impl Rng,
         commands: &mut Commands,
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
@@ -85,7 +108,6 @@ impl Asteroid {
         };
 
         // Generate a random asteroid
-        let mut rng = rand::rng();
         let mut asteroid_points = (0..asteroid_parameters.number_of_points)
             .map(|i| {
                 let random = rng.random_range(0.0..1.0);
@@ -112,26 +134,34 @@ impl Asteroid {
         // needs to connect to the first point to draw a closed shape.
         asteroid_points.push(asteroid_points[0]);
 
+        let positions = asteroid_points
+            .iter()
+            // Here we use the angle and radius to calculate the position of the point
+            // using polar coordinates. The cosine of the angle `c` times its radius gives the x coordinate,
+            // and the sine of the angle `c` times the point's radius gives the y coordinate.
+            .map(|(c, angle)| Vec3::new(c.cos() * angle, c.sin() * angle, 5.0))
+            .collect::<Vec<_>>();
+
+        // Collide with the same outline that is drawn.
+        let polygon = CollisionPolygon::new(&positions);
+
         let mesh = Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::all())
-            .with_inserted_attribute(
-                Mesh::ATTRIBUTE_POSITION,
-                asteroid_points
-                    .iter()
-                    // Here we use the angle and radius to calculate the position of the point
-                    // using polar coordinates. The cosine of the angle `c` times its radius gives the x coordinate,
-                    // and the sine of the angle `c` times the point's radius gives the y coordinate.
-                    .map(|(c, angle)| Vec3::new(c.cos() * angle, c.sin() * angle, 5.0))
-                    .collect::<Vec<_>>(),
-            );
-
-        let asteroid = Asteroid { size, direction };
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+        let asteroid = Asteroid { size };
 
         // Spawn a list of lines with start and end points for each lines
         commands.spawn((
             asteroid,
+            Velocity(velocity),
             Mesh2d(meshes.add(mesh)),
             MeshMaterial2d(materials.add(ColorMaterial::from_color(Color::WHITE))),
             Transform::from_xyz(location.x, location.y, location.z),
+            polygon,
+            TransformInterpolation::default(),
+            WrapGhosts {
+                radius: size.diameter(),
+            },
         ));
     }
 }
@@ -1,47 +1,66 @@
 //! Systems for managing asteroids in the game.
 
-use super::{Asteroid, AsteroidSize};
-use bevy::{prelude::*, window::PrimaryWindow};
+use super::{Asteroid, AsteroidSize, AsteroidSpawnTimer};
+use crate::{
+    RunClock,
+    arena::{Arena, wrap_transform},
+    interpolation::TransformInterpolation,
+    physics::Velocity,
+    rng::GameRng,
+    ship::PlayerShip,
+};
+use bevy::prelude::*;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 
-/// Moves the asteroids based on their direction. Speed is determined by
-/// the x and y components of the direction vector.
+/// Moves the asteroids along their velocity.
 ///
 /// # Arguments
 /// * `time`: The `time` resource to calculate the movement delta.
-/// * `query`: A query that retrieves every `Asteroid` and its `Transform`.
-pub fn move_asteroids(time: Res<Time>, mut query: Query<(&Asteroid, &mut Transform)>) {
-    for (asteroid, mut transform) in query.iter_mut() {
-        transform.translation +=
-            Vec3::new(asteroid.direction.x, asteroid.direction.y, 0.0) * time.delta_secs();
+/// * `query`: A query that retrieves the `Velocity` and `Transform` of every `Asteroid`.
+pub fn move_asteroids(
+    time: Res<Time>,
+    mut query: Query<(&Velocity, &mut Transform), With<Asteroid>>,
+) {
+    for (velocity, mut transform) in query.iter_mut() {
+        velocity.advance(&mut transform, time.delta_secs());
     }
 }
 
-/// Checks and fixes asteroids so that they are within the bounds of the game window.
+/// Checks and fixes asteroids so that they are within the bounds of the arena.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to despawn asteroids that are out of bounds.
 /// * `query`: A query that retrieves every `Asteroid` and its `Transform`.
-/// * `window`: A query that retrieves the primary window to get its size.
+/// * `arena`: The `Arena` resource to get the size of the playfield.
 pub fn check_asteroid_bounds(
     mut commands: Commands,
-    mut query: Query<(Entity, &Asteroid, &Transform)>,
-    window: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<(
+        Entity,
+        &Asteroid,
+        &mut Transform,
+        Option<&mut TransformInterpolation>,
+    )>,
+    arena: Res<Arena>,
 ) {
-    // Get the window size
-    let window = window.single().unwrap();
+    let arena_size = arena.size;
 
-    let window_size = Vec2::new(window.width(), window.height());
+    for (entity, asteroid, mut transform, interpolation) in query.iter_mut() {
+        // In a wrapping arena asteroids come back around instead.
+        if arena.wraps() {
+            wrap_transform(&arena, &mut transform, interpolation);
+            continue;
+        }
 
-    for (entity, asteroid, transform) in query.iter_mut() {
         // Get the asteroid width
         let asteroid_diameter = asteroid.size.diameter();
 
         // Check if the asteroid is out of bounds
         // If the asteroid is out of bounds, despawn it - it will never return to the screen
-        if transform.translation.x + asteroid_diameter < -window_size.x / 2.0
-            || transform.translation.x - asteroid_diameter > window_size.x / 2.0
-            || transform.translation.y + asteroid_diameter < -window_size.y / 2.0
-            || transform.translation.y - asteroid_diameter > window_size.y / 2.0
+        if transform.translation.x + asteroid_diameter < -arena_size.x / 2.0
+            || transform.translation.x - asteroid_diameter > arena_size.x / 2.0
+            || transform.translation.y + asteroid_diameter < -arena_size.y / 2.0
+            || transform.translation.y - asteroid_diameter > arena_size.y / 2.0
         {
             // Remove the asteroid
             commands.entity(entity).despawn();
@@ -49,72 +68,130 @@ pub fn check_asteroid_bounds(
     }
 }
 
-/// Spawns new asteroids based on the game state and window size.
+/// Returns how hard the asteroid spawner is going at this point of the run.
+///
+/// This starts at 1 and slowly grows. More and bigger asteroids spawn as it goes up.
+///
+/// # Arguments
+/// * `run_clock`: The `RunClock` resource with how long the run has been going.
+pub fn spawn_difficulty(run_clock: &RunClock) -> f32 {
+    // The longer the run is going, the more asteroids will spawn.
+    // This is done by using a logarithmic function to adjust the time between spawns.
+    // In this case I found that a logarithmic function with a base of 5.0 works well.
+    // The time elapsed is divided by 2.0 to make the game easier at the start,
+    // and then the logarithm is applied to it.
+    let log_base = 5.0;
+    let time_elapsed = (run_clock.0.elapsed_secs() / 2.0) + log_base;
+    time_elapsed.log(log_base)
+}
+
+/// The most asteroids that can be in a wrapping arena at once.
+pub const WRAPPING_ASTEROID_LIMIT: usize = 40;
+
+/// How far from the player ship asteroids have to spawn in a wrapping arena, where they
+/// spawn in sight.
+pub const WRAPPING_SPAWN_CLEARANCE: f32 = 150.0;
+
+/// Spawns new asteroids based on the game state and arena size.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to spawn new asteroids.
 /// * `meshes`: The `Assets<Mesh>` resource to create the asteroid mesh.
 /// * `materials`: The `Assets<ColorMaterial>` resource to create the asteroid material.
-/// * `window`: A query that retrieves the primary window to get its size.
-/// * `time`: The `Time` resource to determine the frequency of asteroid spawning.
+/// * `arena`: The `Arena` resource to get the size of the playfield.
+/// * `run_clock`: The `RunClock` resource to determine the frequency of asteroid spawning.
+/// * `rng`: The `GameRng` resource to pick the asteroids' sizes, locations and shapes.
+/// * `spawn_timer`: The `AsteroidSpawnTimer` resource that decides when asteroids can spawn.
+/// * `time`: The `Time` resource to advance the spawn timer.
+/// * `asteroids`: A query that retrieves every `Asteroid`, to limit how many there are.
+/// * `ships`: A query that retrieves the player ship's `Transform`, to keep clear of it.
 pub fn spawn_asteroids(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    window: Query<&Window, With<PrimaryWindow>>,
+    arena: Res<Arena>,
+    run_clock: Res<RunClock>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_timer: ResMut<AsteroidSpawnTimer>,
     time: Res<Time>,
+    asteroids: Query<(), With<Asteroid>>,
+    ships: Query<&Transform, With<PlayerShip>>,
 ) {
-    // The longer the game is running, the more asteroids will spawn.
-    // This is done by using a logarithmic function to adjust the time between spawns.
-    // In this case I found that a logarithmic function with a base of 5.0 works well.
-    // The time elapsed is divided by 2.0 to make the game easier at the start,
-    // and then the logarithm is applied to it.
-    let log_base = 5.0;
-    let time_elapsed = (time.elapsed_secs() / 2.0) + log_base;
-    let time_adjusted = time_elapsed.log(log_base);
+    if !spawn_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // Asteroids never leave a wrapping arena, so stop before it fills up.
+    if arena.wraps() && asteroids.iter().count() >= WRAPPING_ASTEROID_LIMIT {
+        return;
+    }
 
-    let window = window.single().unwrap();
+    let time_adjusted = spawn_difficulty(&run_clock);
+
+    let GameRng {
+        spawning, shapes, ..
+    } = &mut *rng;
 
     // We use a while loop to spawn a random number of asteroids.
-    while rand::random_range(0.0..time_adjusted) > 1.0 {
+    while spawning.random_range(0.0..time_adjusted) > 1.0 {
         // Pick a size for the asteroid - the longer the game is running, the bigger the asteroid,
         // and the more frequent larger asteroids will spawn.
-        let size = match rand::random_range(0.0..time_adjusted) {
+        let size = match spawning.random_range(0.0..time_adjusted) {
             0.0..1.5 => AsteroidSize::Small,
             1.5..2.5 => AsteroidSize::Medium,
             _ => AsteroidSize::Large,
         };
 
-        let asteroid_diameter = size.diameter();
+        // Asteroids start just out of sight. In a wrapping arena that is just inside the
+        // edge, as anything past it is drawn on the opposite side.
+        let edge_distance = if arena.wraps() {
+            -size.diameter()
+        } else {
+            size.diameter()
+        };
+
+        // Picks where along an edge of the given length the asteroid starts.
+        let along_edge = |spawning: &mut ChaCha8Rng, length: f32| {
+            if arena.wraps() {
+                // Keep clear of the corners too, so no part of the asteroid wraps around.
+                let reach = (length / 2.0 - size.diameter()).max(0.0);
+                spawning.random_range(-reach..=reach)
+            } else {
+                spawning.random_range(-length / 2.0..length / 2.0)
+            }
+        };
 
         // Pick a location for the asteroid.
         // TODO: Don't match on an integer!
-        let (location, mut dir) = match rand::random_range(0..4) {
+        let (location, mut dir) = match spawning.random_range(0..4) {
             0 => {
                 // The top of the screen
                 (
                     Vec3::new(
-                        rand::random_range(-window.width() / 2.0..window.width() / 2.0),
-                        window.height() / 2.0 + asteroid_diameter,
+                        along_edge(spawning, arena.width()),
+                        arena.height() / 2.0 + edge_distance,
                         0.0,
                     ),
                     // Pick anywhere pointing down
-                    Vec2::new(rand::random_range(-1.0..1.0), rand::random_range(-1.0..0.1))
-                        .normalize(),
+                    Vec2::new(
+                        spawning.random_range(-1.0..1.0),
+                        spawning.random_range(-1.0..0.1),
+                    )
+                    .normalize(),
                 )
             }
             1 => {
                 // The right side of the screen
                 (
                     Vec3::new(
-                        window.width() / 2.0 + asteroid_diameter,
-                        rand::random_range(-window.height() / 2.0..window.height() / 2.0),
+                        arena.width() / 2.0 + edge_distance,
+                        along_edge(spawning, arena.height()),
                         0.0,
                     ),
                     // Pick anywhere pointing left
                     Vec2::new(
-                        rand::random_range(-1.0..-0.1),
-                        rand::random_range(-1.0..1.0),
+                        spawning.random_range(-1.0..-0.1),
+                        spawning.random_range(-1.0..1.0),
                     )
                     .normalize(),
                 )
@@ -123,31 +200,50 @@ pub fn spawn_asteroids(
                 // The bottom of the screen
                 (
                     Vec3::new(
-                        rand::random_range(-window.width() / 2.0..window.width() / 2.0),
-                        -window.height() / 2.0 - asteroid_diameter,
+                        along_edge(spawning, arena.width()),
+                        -arena.height() / 2.0 - edge_distance,
                         0.0,
                     ),
                     // Pick anywhere pointing up
-                    Vec2::new(rand::random_range(-1.0..1.0), rand::random_range(0.1..1.0))
-                        .normalize(),
+                    Vec2::new(
+                        spawning.random_range(-1.0..1.0),
+                        spawning.random_range(0.1..1.0),
+                    )
+                    .normalize(),
                 )
             }
             3 => {
                 // The left side of the screen
                 (
                     Vec3::new(
-                        -window.width() / 2.0 - asteroid_diameter,
-                        rand::random_range(-window.height() / 2.0..window.height() / 2.0),
+                        -arena.width() / 2.0 - edge_distance,
+                        along_edge(spawning, arena.height()),
                         0.0,
                     ),
                     // Pick anywhere pointing right
-                    Vec2::new(rand::random_range(0.1..1.0), rand::random_range(-1.0..1.0))
-                        .normalize(),
+                    Vec2::new(
+                        spawning.random_range(0.1..1.0),
+                        spawning.random_range(-1.0..1.0),
+                    )
+                    .normalize(),
                 )
             }
             _ => unreachable!(),
         };
 
+        // Don't spawn an asteroid in sight right next to the player.
+        if arena.wraps()
+            && ships.iter().any(|ship| {
+                let ship = ship.translation.truncate();
+                let location = location.truncate();
+                let offset = arena.nearest_copy_offset(ship, location);
+
+                ship.distance(location + offset) < WRAPPING_SPAWN_CLEARANCE + size.diameter()
+            })
+        {
+            continue;
+        }
+
         // Adjust the speed of the asteroid based on its size
         match size {
             AsteroidSize::Small => {
@@ -171,6 +267,7 @@ pub fn spawn_asteroids(
             size,
             location,
             dir,
+            shapes,
             &mut commands,
             &mut meshes,
             &mut materials,
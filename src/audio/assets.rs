@@ -0,0 +1,280 @@
+//! Loads every sound the game plays up front, so none of them hitch on their first play.
+//!
+//! Every sound is listed in the [`Sound`] manifest. Once loading is done, the manifest is
+//! validated: a sound whose file is missing or broken is reported once, and is replaced by
+//! a generated placeholder tone so that the game still gives audible feedback.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::LoadState,
+    audio::{Pitch, Volume},
+    platform::collections::HashMap,
+    prelude::*,
+};
+
+use super::{AudioBus, AudioChannel, Voice};
+use crate::GameState;
+
+/// How loud placeholder tones play, as a pure sine wave is much louder than the real sounds.
+const PLACEHOLDER_VOLUME: f32 = 0.2;
+
+/// Every sound the game plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    MainSong,
+    Laser,
+    AsteroidLargeDestruction,
+    AsteroidMediumDestruction,
+    AsteroidSmallDestruction,
+    PlayerHit,
+    PlayerDestroyed,
+}
+
+impl Sound {
+    /// The manifest of every sound.
+    pub const ALL: [Sound; 7] = [
+        Sound::MainSong,
+        Sound::Laser,
+        Sound::AsteroidLargeDestruction,
+        Sound::AsteroidMediumDestruction,
+        Sound::AsteroidSmallDestruction,
+        Sound::PlayerHit,
+        Sound::PlayerDestroyed,
+    ];
+
+    /// Returns the path of the sound's file in the assets folder.
+    pub fn path(self) -> &'static str {
+        match self {
+            Sound::MainSong => "audio/Eternity.mp3",
+            Sound::Laser => "audio/laser.mp3",
+            Sound::AsteroidLargeDestruction => "audio/asteroid_large_destruction.mp3",
+            Sound::AsteroidMediumDestruction => "audio/asteroid_medium_destruction.mp3",
+            Sound::AsteroidSmallDestruction => "audio/asteroid_small_destruction.mp3",
+            Sound::PlayerHit => "audio/player_hit.mp3",
+            Sound::PlayerDestroyed => "audio/player_destroyed.mp3",
+        }
+    }
+
+    /// Returns the tone played in place of the sound if its file can't be loaded.
+    ///
+    /// Bigger events get lower and longer tones.
+    pub fn placeholder(self) -> Pitch {
+        let (frequency, millis) = match self {
+            Sound::MainSong => (110.0, 4_000),
+            Sound::Laser => (880.0, 60),
+            Sound::AsteroidLargeDestruction => (110.0, 300),
+            Sound::AsteroidMediumDestruction => (165.0, 200),
+            Sound::AsteroidSmallDestruction => (220.0, 120),
+            Sound::PlayerHit => (330.0, 150),
+            Sound::PlayerDestroyed => (82.5, 600),
+        };
+
+        Pitch::new(frequency, Duration::from_millis(millis))
+    }
+
+    /// Returns how many voices of the sound can play at once.
+    pub fn max_voices(self) -> usize {
+        match self {
+            Sound::MainSong | Sound::PlayerDestroyed => 1,
+            Sound::Laser => 4,
+            Sound::AsteroidLargeDestruction
+            | Sound::AsteroidMediumDestruction
+            | Sound::AsteroidSmallDestruction => 3,
+            Sound::PlayerHit => 2,
+        }
+    }
+}
+
+/// Where a sound is played from.
+#[derive(Debug, Clone)]
+pub enum SoundSource {
+    /// The sound's file.
+    File(Handle<AudioSource>),
+    /// A placeholder tone, for a sound whose file couldn't be loaded.
+    Placeholder(Handle<Pitch>),
+}
+
+impl SoundSource {
+    /// Spawns an audio player for the sound on the given bus, and returns its entity.
+    ///
+    /// # Arguments
+    /// * `commands`: The `Commands` to spawn the audio player.
+    /// * `settings`: How to play the sound.
+    /// * `bus`: The `AudioBus` whose volume the sound plays at.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        settings: PlaybackSettings,
+        bus: AudioBus,
+    ) -> Entity {
+        match self {
+            SoundSource::File(handle) => commands
+                .spawn((
+                    AudioPlayer::new(handle.clone()),
+                    settings,
+                    AudioChannel {
+                        bus,
+                        volume: settings.volume,
+                    },
+                ))
+                .id(),
+            SoundSource::Placeholder(handle) => {
+                let volume = settings.volume * Volume::Linear(PLACEHOLDER_VOLUME);
+
+                commands
+                    .spawn((
+                        AudioPlayer(handle.clone()),
+                        settings.with_volume(volume),
+                        AudioChannel { bus, volume },
+                    ))
+                    .id()
+            }
+        }
+    }
+}
+
+/// Where to play every sound in the [`Sound`] manifest from.
+#[derive(Resource, Debug, Clone)]
+pub struct AudioAssets {
+    sounds: HashMap<Sound, SoundSource>,
+    /// Whether `validate_audio_assets` has checked every sound since it finished loading.
+    validated: bool,
+}
+
+impl AudioAssets {
+    /// Starts loading every sound.
+    pub fn load(asset_server: &AssetServer) -> Self {
+        let sounds = Sound::ALL
+            .into_iter()
+            .map(|sound| (sound, SoundSource::File(asset_server.load(sound.path()))))
+            .collect();
+
+        Self {
+            sounds,
+            validated: false,
+        }
+    }
+
+    /// Returns where to play a sound from.
+    pub fn get(&self, sound: Sound) -> &SoundSource {
+        &self.sounds[&sound]
+    }
+
+    /// Plays a sound effect as a new voice of the sound, panned towards where it happened,
+    /// and returns its entity.
+    ///
+    /// # Arguments
+    /// * `commands`: The `Commands` to spawn the audio player.
+    /// * `sound`: The sound effect to play.
+    /// * `position`: Where the sound's event happened in the world.
+    pub fn play_effect(&self, commands: &mut Commands, sound: Sound, position: Vec2) -> Entity {
+        let voice = self.get(sound).spawn(
+            commands,
+            PlaybackSettings::DESPAWN.with_spatial(true),
+            AudioBus::Effects,
+        );
+
+        commands.entity(voice).insert((
+            Voice(sound),
+            Transform::from_translation(position.extend(0.0)),
+        ));
+        voice
+    }
+
+    /// Returns whether every sound is loaded, or has been replaced by a placeholder.
+    pub fn is_ready(&self) -> bool {
+        self.validated
+    }
+
+    /// Returns how many of the sounds are done loading, and how many there are.
+    ///
+    /// A sound that failed to load is done, as waiting for it won't help.
+    pub fn progress(&self, asset_server: &AssetServer) -> (usize, usize) {
+        let done = self
+            .sounds
+            .values()
+            .filter(|source| match source {
+                SoundSource::File(handle) => matches!(
+                    asset_server.get_load_state(handle.id()),
+                    Some(LoadState::Loaded | LoadState::Failed(_))
+                ),
+                SoundSource::Placeholder(_) => true,
+            })
+            .count();
+
+        (done, self.sounds.len())
+    }
+}
+
+/// Starts loading every sound into the `AudioAssets` resource.
+///
+/// This has to be done after the `Startup` stage, otherwise the loading of assets will
+/// break WASM builds.
+///
+/// # Arguments
+/// * `commands`: The `Commands` to insert the `AudioAssets` resource.
+/// * `asset_server`: The `AssetServer` resource to load the sounds.
+pub fn load_audio_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AudioAssets::load(&asset_server));
+}
+
+/// Checks every sound once they are done loading, and replaces the ones that failed with
+/// placeholder tones.
+///
+/// # Arguments
+/// * `audio_assets`: The `AudioAssets` resource to validate.
+/// * `asset_server`: The `AssetServer` resource to check the sounds' load states.
+/// * `pitches`: The `Assets<Pitch>` resource to add the placeholder tones to.
+pub fn validate_audio_assets(
+    audio_assets: Option<ResMut<AudioAssets>>,
+    asset_server: Res<AssetServer>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    let Some(mut audio_assets) = audio_assets else {
+        return;
+    };
+
+    if audio_assets.validated {
+        return;
+    }
+
+    let (done, total) = audio_assets.progress(&asset_server);
+    if done < total {
+        return;
+    }
+
+    for sound in Sound::ALL {
+        let SoundSource::File(handle) = audio_assets.get(sound) else {
+            continue;
+        };
+
+        if let Some(LoadState::Failed(error)) = asset_server.get_load_state(handle.id()) {
+            log::warn!(
+                "Sound `{}` could not be loaded, playing a placeholder tone instead: {error}",
+                sound.path()
+            );
+
+            let placeholder = pitches.add(sound.placeholder());
+            audio_assets
+                .sounds
+                .insert(sound, SoundSource::Placeholder(placeholder));
+        }
+    }
+
+    audio_assets.validated = true;
+}
+
+/// Moves on to the main menu once every sound is ready to play.
+///
+/// # Arguments
+/// * `audio_assets`: The `AudioAssets` resource being loaded.
+/// * `next_state`: The `NextState<GameState>` resource to leave the loading screen.
+pub fn finish_loading(
+    audio_assets: Option<Res<AudioAssets>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if audio_assets.is_some_and(|audio_assets| audio_assets.is_ready()) {
+        next_state.set(GameState::MainMenu);
+    }
+}
@@ -2,6 +2,7 @@
 
 use bevy::prelude::*;
 
+use super::{AudioAssets, Sound};
 use crate::asteroid::AsteroidSize;
 
 /// Plays a sound for destroying an asteroid based on its size.
@@ -9,21 +10,19 @@ use crate::asteroid::AsteroidSize;
 /// # Arguments
 /// * `commands`: The `Commands` resource to spawn the audio player entity.
 /// * `size`: The size of the asteroid being destroyed.
-/// * `asset_server`: The `AssetServer` resource to load the asteroid destruction sound asset.
+/// * `position`: Where the asteroid was destroyed.
+/// * `audio_assets`: The `AudioAssets` resource with the asteroid destruction sounds.
 pub fn destroy_asteroid(
     commands: &mut Commands,
     size: AsteroidSize,
-    asset_server: &Res<AssetServer>,
+    position: Vec2,
+    audio_assets: &AudioAssets,
 ) {
     let sound = match size {
-        AsteroidSize::Large => "audio/asteroid_large_destruction.mp3",
-        AsteroidSize::Medium => "audio/asteroid_medium_destruction.mp3",
-        AsteroidSize::Small => "audio/asteroid_small_destruction.mp3",
+        AsteroidSize::Large => Sound::AsteroidLargeDestruction,
+        AsteroidSize::Medium => Sound::AsteroidMediumDestruction,
+        AsteroidSize::Small => Sound::AsteroidSmallDestruction,
     };
 
-    // If found 0.75 to be pleasantly loud, but not too loud for these sounds.
-    commands.spawn((
-        AudioPlayer::new(asset_server.load(sound)),
-        PlaybackSettings::REMOVE,
-    ));
+    audio_assets.play_effect(commands, sound, position);
 }
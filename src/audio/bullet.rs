@@ -2,14 +2,14 @@
 
 use bevy::prelude::*;
 
+use super::{AudioAssets, Sound};
+
 /// Plays a sound for firing a bullet.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to spawn the audio player entity.
-/// * `asset_server`: The `AssetServer` resource to load the bullet sound asset.
-pub fn fire_bullet(commands: &mut Commands, asset_server: &Res<AssetServer>) {
-    commands.spawn((
-        AudioPlayer::new(asset_server.load("audio/laser.mp3")),
-        PlaybackSettings::REMOVE,
-    ));
+/// * `position`: Where the bullet was fired from.
+/// * `audio_assets`: The `AudioAssets` resource with the bullet sound.
+pub fn fire_bullet(commands: &mut Commands, position: Vec2, audio_assets: &AudioAssets) {
+    audio_assets.play_effect(commands, Sound::Laser, position);
 }
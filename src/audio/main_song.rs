@@ -1,15 +1,153 @@
 //! Audio playback for the main song in the game.
+//!
+//! The song follows how much danger the player is in. It plays quieter and at its normal
+//! speed while things are calm, and louder and a little faster as the screen fills with
+//! asteroids, the ship gets damaged and the spawner picks up. It also dips for a moment
+//! when the ship is hit.
 
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{audio::Volume, prelude::*};
+
+use super::{AudioAssets, AudioBus, Sound, SoundSink};
+use crate::{
+    InGame, RunClock,
+    asteroid::{Asteroid, spawn_difficulty},
+    ship::PlayerShip,
+};
+
+/// How loud the song plays when things are calm, relative to its full volume.
+pub const CALM_MUSIC_VOLUME: f32 = 0.7;
+
+/// How fast the song plays at its most intense, relative to its normal speed.
+pub const INTENSE_MUSIC_SPEED: f32 = 1.06;
+
+/// How many asteroids on screen make the song as intense as it gets from asteroids alone.
+pub const INTENSE_ASTEROID_COUNT: usize = 25;
+
+/// How much the intensity can change per second, so the song doesn't jump around.
+pub const MUSIC_INTENSITY_RATE: f32 = 0.4;
+
+/// How loud the song plays right after the ship is hit, relative to its current volume.
+pub const DUCKED_MUSIC_VOLUME: f32 = 0.35;
+
+/// How long it takes the song to come back up after the ship is hit.
+pub const MUSIC_DUCK_DURATION: Duration = Duration::from_millis(800);
+
+/// Marker component for the main song's audio player.
+#[derive(Component)]
+pub struct MainSong;
+
+/// Sent to turn the song down for a moment.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DuckMusic;
+
+/// How intense the song currently is.
+#[derive(Resource, Debug)]
+pub struct MusicIntensity {
+    /// How intense the song is, from 0 for calm to 1 for as intense as it gets.
+    pub level: f32,
+    /// Counts up from the last time the song was ducked.
+    pub duck: Timer,
+}
+
+impl Default for MusicIntensity {
+    fn default() -> Self {
+        let mut duck = Timer::new(MUSIC_DUCK_DURATION, TimerMode::Once);
+        duck.tick(MUSIC_DUCK_DURATION);
+
+        Self { level: 0.0, duck }
+    }
+}
+
+impl MusicIntensity {
+    /// Returns how loud the song plays, relative to its full volume.
+    pub fn volume(&self) -> Volume {
+        let duck = DUCKED_MUSIC_VOLUME.lerp(1.0, self.duck.fraction());
+
+        Volume::Linear(CALM_MUSIC_VOLUME.lerp(1.0, self.level) * duck)
+    }
+
+    /// Returns how fast the song plays, relative to its normal speed.
+    pub fn speed(&self) -> f32 {
+        1.0.lerp(INTENSE_MUSIC_SPEED, self.level)
+    }
+}
 
 /// Plays the main song of the game.
 ///
 /// # Arguments
 /// * `commands` - The Bevy commands to spawn the audio player.
-/// * `asset_server` - The Bevy asset server to load the audio file.
-pub fn play_main_song(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        AudioPlayer::new(asset_server.load("audio/Eternity.mp3")),
+/// * `audio_assets` - The `AudioAssets` resource with the song.
+pub fn play_main_song(mut commands: Commands, audio_assets: Res<AudioAssets>) {
+    let song = audio_assets.get(Sound::MainSong).spawn(
+        &mut commands,
         PlaybackSettings::LOOP,
-    ));
+        AudioBus::Music,
+    );
+
+    commands.entity(song).insert(MainSong);
+}
+
+/// Turns the song down for a moment when the ship is hit.
+///
+/// # Arguments
+/// * `commands` - The Bevy commands to send the event.
+pub fn duck_music(commands: &mut Commands) {
+    commands.send_event(DuckMusic);
+}
+
+/// Moves the song's intensity towards how much danger the player is in, and plays the song
+/// at the speed for its intensity. `mix_audio` plays it at the volume for its intensity.
+///
+/// The danger comes from how many asteroids there are, how damaged the ship is, and how
+/// hard the asteroid spawner is going. Outside of a run, the song calms down.
+///
+/// # Arguments
+/// * `intensity` - The `MusicIntensity` resource to update.
+/// * `duck_events` - The `EventReader` for requests to duck the song.
+/// * `time` - The `Time` resource to move the intensity and duck smoothly.
+/// * `in_game` - The `InGame` state, if a run is being played.
+/// * `run_clock` - The `RunClock` resource to get the spawner's difficulty.
+/// * `asteroids` - A query that retrieves every asteroid.
+/// * `players` - A query that retrieves the player ship.
+/// * `songs` - A query that retrieves the main song's sink.
+pub fn update_music_intensity(
+    mut intensity: ResMut<MusicIntensity>,
+    mut duck_events: EventReader<DuckMusic>,
+    time: Res<Time>,
+    in_game: Option<Res<State<InGame>>>,
+    run_clock: Res<RunClock>,
+    asteroids: Query<(), With<Asteroid>>,
+    players: Query<&PlayerShip>,
+    mut songs: Query<SoundSink, With<MainSong>>,
+) {
+    if duck_events.read().count() > 0 {
+        intensity.duck.reset();
+    }
+    intensity.duck.tick(time.delta());
+
+    let target = if in_game.is_some() {
+        let crowding = asteroids.iter().count() as f32 / INTENSE_ASTEROID_COUNT as f32;
+        let damage = players
+            .iter()
+            .map(|player| 1.0 - player.health as f32 / 100.0)
+            .fold(0.0, f32::max);
+        // The difficulty starts at 1, and gets to 3 after 4 minutes.
+        let difficulty = (spawn_difficulty(&run_clock) - 1.0) / 2.0;
+
+        (crowding.min(1.0) * 0.4 + damage.clamp(0.0, 1.0) * 0.3 + difficulty.min(1.0) * 0.3)
+            .clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let max_change = MUSIC_INTENSITY_RATE * time.delta_secs();
+    intensity.level += (target - intensity.level).clamp(-max_change, max_change);
+
+    for mut song in songs.iter_mut() {
+        if let Some(sink) = song.playback() {
+            sink.set_speed(intensity.speed());
+        }
+    }
 }
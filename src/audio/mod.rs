@@ -2,7 +2,48 @@
 //!
 //! This module contains audio playback functionality for the game.
 
+pub mod assets;
+pub use assets::*;
 pub mod asteroid;
 pub mod bullet;
 pub mod main_song;
+pub mod settings;
+pub use settings::*;
+pub mod spatial;
+pub use spatial::*;
 pub mod ship;
+pub mod voices;
+pub use voices::*;
+
+use bevy::{prelude::*, transform::TransformSystem};
+use main_song::{MusicIntensity, update_music_intensity};
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioSettings>();
+        app.init_resource::<AudioSettingsFile>();
+        app.add_systems(Startup, load_audio_settings);
+
+        // Limit and vary the voices of every sound effect.
+        app.init_resource::<Voices>();
+        app.init_resource::<VoiceRng>();
+
+        // Sounds are spawned up to the end of `Update`, so they are only set up right before
+        // they start playing, which Bevy does after propagating transforms.
+        app.add_systems(
+            PostUpdate,
+            (manage_voices, apply_audio_settings_to_new_sounds, mix_audio)
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        );
+
+        // Make the music follow the danger the player is in.
+        app.init_resource::<MusicIntensity>();
+        app.add_systems(Update, update_music_intensity);
+
+        // Pan the sound effects towards where they happened.
+        app.add_systems(Update, sync_spatial_audio);
+    }
+}
@@ -0,0 +1,202 @@
+//! The player's volume settings, applied to every sound as it plays.
+
+use std::{io, path::PathBuf};
+
+use bevy::{audio::Volume, platform::collections::HashMap, prelude::*};
+
+use super::{SoundSink, main_song::MusicIntensity};
+use crate::{
+    pause::{PAUSED_MUSIC_VOLUME, PauseState},
+    storage::{data_file, load_settings, save_settings},
+};
+
+/// How much a volume changes with every step in the settings menu.
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// The group of sounds a sound's volume is set with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioBus {
+    Music,
+    Effects,
+}
+
+/// The bus a sound plays on, and how loud the sound itself is.
+///
+/// Every sound played through `SoundSource::spawn` has one, and plays at its own volume
+/// scaled by the `AudioSettings` for its bus.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AudioChannel {
+    pub bus: AudioBus,
+    /// The volume of the sound before the `AudioSettings` are applied.
+    pub volume: Volume,
+}
+
+/// How loud the player wants the game to be. Every volume goes from 0 to 1.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct AudioSettings {
+    /// Scales every sound.
+    pub master: f32,
+    /// Scales the music.
+    pub music: f32,
+    /// Scales the sound effects.
+    pub effects: f32,
+    /// Silences every sound without changing the volumes.
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            effects: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Returns how much the sounds on a bus are scaled by.
+    pub fn volume(&self, bus: AudioBus) -> Volume {
+        if self.muted {
+            return Volume::SILENT;
+        }
+
+        let bus_volume = match bus {
+            AudioBus::Music => self.music,
+            AudioBus::Effects => self.effects,
+        };
+
+        Volume::Linear(self.master * bus_volume)
+    }
+
+    /// Returns the settings as `key value` pairs for a settings file.
+    pub fn to_entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("master", self.master.to_string()),
+            ("music", self.music.to_string()),
+            ("effects", self.effects.to_string()),
+            ("muted", self.muted.to_string()),
+        ]
+    }
+
+    /// Updates the settings from the `key value` pairs of a settings file.
+    ///
+    /// Missing or invalid values are left as they are.
+    pub fn apply_entries(&mut self, entries: &HashMap<String, String>) {
+        let volume = |key: &str| {
+            entries
+                .get(key)
+                .and_then(|value| value.parse::<f32>().ok())
+                .map(|value| value.clamp(0.0, 1.0))
+        };
+
+        self.master = volume("master").unwrap_or(self.master);
+        self.music = volume("music").unwrap_or(self.music);
+        self.effects = volume("effects").unwrap_or(self.effects);
+        self.muted = entries
+            .get("muted")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(self.muted);
+    }
+}
+
+/// Where the `AudioSettings` are saved between sessions. `None` keeps them in memory only.
+#[derive(Resource, Debug, Clone)]
+pub struct AudioSettingsFile(pub Option<PathBuf>);
+
+impl Default for AudioSettingsFile {
+    fn default() -> Self {
+        Self(data_file("audio.txt"))
+    }
+}
+
+/// Loads the `AudioSettings` from their file when the game starts.
+///
+/// # Arguments
+/// * `settings`: The `AudioSettings` resource to load into.
+/// * `file`: The `AudioSettingsFile` resource with the path to load from.
+pub fn load_audio_settings(mut settings: ResMut<AudioSettings>, file: Res<AudioSettingsFile>) {
+    let Some(path) = &file.0 else {
+        return;
+    };
+
+    match load_settings(path) {
+        Ok(entries) => settings.apply_entries(&entries),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::error!(
+            "Failed to load audio settings from {}: {error}",
+            path.display()
+        ),
+    }
+}
+
+/// Saves the `AudioSettings` to their file, if they have one.
+///
+/// # Arguments
+/// * `settings`: The `AudioSettings` resource to save.
+/// * `file`: The `AudioSettingsFile` resource with the path to save to.
+pub fn save_audio_settings(settings: Res<AudioSettings>, file: Res<AudioSettingsFile>) {
+    let Some(path) = &file.0 else {
+        return;
+    };
+
+    if let Err(error) = save_settings(path, &settings.to_entries()) {
+        log::error!(
+            "Failed to save audio settings to {}: {error}",
+            path.display()
+        );
+    }
+}
+
+/// Scales new sounds by the `AudioSettings` before they start playing.
+///
+/// # Arguments
+/// * `settings`: The `AudioSettings` resource to scale the sounds by.
+/// * `sounds`: A query that retrieves the `AudioChannel` and `PlaybackSettings` of every new sound.
+pub fn apply_audio_settings_to_new_sounds(
+    settings: Res<AudioSettings>,
+    mut sounds: Query<(&AudioChannel, &mut PlaybackSettings), Added<AudioChannel>>,
+) {
+    for (channel, mut playback) in sounds.iter_mut() {
+        playback.volume = channel.volume * settings.volume(channel.bus);
+    }
+}
+
+/// Keeps the volume of every playing sound in line with the `AudioSettings`, so changes
+/// are heard right away. The music also follows its `MusicIntensity`, and is turned down
+/// while the game is paused.
+///
+/// # Arguments
+/// * `settings`: The `AudioSettings` resource to scale the sounds by.
+/// * `music_intensity`: The `MusicIntensity` resource to scale the music by.
+/// * `pause_state`: The `PauseState`, if a run is being played.
+/// * `sinks`: A query that retrieves every playing sound and its `AudioChannel`.
+pub fn mix_audio(
+    settings: Res<AudioSettings>,
+    music_intensity: Res<MusicIntensity>,
+    pause_state: Option<Res<State<PauseState>>>,
+    mut sinks: Query<(SoundSink, &AudioChannel)>,
+) {
+    let paused = pause_state.is_some_and(|state| *state.get() == PauseState::Paused);
+
+    for (mut sink, channel) in sinks.iter_mut() {
+        let Some(sink) = sink.playback() else {
+            continue;
+        };
+
+        let mut volume = channel.volume * settings.volume(channel.bus);
+
+        if channel.bus == AudioBus::Music {
+            volume *= music_intensity.volume();
+
+            if paused {
+                volume *= Volume::Linear(PAUSED_MUSIC_VOLUME);
+            }
+        }
+
+        if sink.volume() != volume {
+            sink.set_volume(volume);
+        }
+    }
+}
@@ -2,26 +2,25 @@
 
 use bevy::prelude::*;
 
-/// Plays a sound for the ship being hit.
+use super::{AudioAssets, Sound, main_song::duck_music};
+
+/// Plays a sound for the ship being hit, and turns the music down for a moment.
 ///
 /// # Arguments
 /// * `commands` - The Bevy commands to spawn the audio player.
-/// * `asset_server` - The Bevy asset server to load the audio file.
-pub fn ship_hit(commands: &mut Commands, asset_server: &Res<AssetServer>) {
-    commands.spawn((
-        AudioPlayer::new(asset_server.load("audio/player_hit.mp3")),
-        PlaybackSettings::REMOVE,
-    ));
+/// * `position` - Where the ship was hit.
+/// * `audio_assets` - The `AudioAssets` resource with the sound.
+pub fn ship_hit(commands: &mut Commands, position: Vec2, audio_assets: &AudioAssets) {
+    audio_assets.play_effect(commands, Sound::PlayerHit, position);
+    duck_music(commands);
 }
 
 /// Handles the audio for the ship being destroyed.
 ///
 /// # Arguments
 /// * `commands` - The Bevy commands to spawn the audio player.
-/// * `asset_server` - The Bevy asset server to load the audio file.
-pub fn ship_destroyed(commands: &mut Commands, asset_server: &Res<AssetServer>) {
-    commands.spawn((
-        AudioPlayer::new(asset_server.load("audio/player_destroyed.mp3")),
-        PlaybackSettings::REMOVE,
-    ));
+/// * `position` - Where the ship was destroyed.
+/// * `audio_assets` - The `AudioAssets` resource with the sound.
+pub fn ship_destroyed(commands: &mut Commands, position: Vec2, audio_assets: &AudioAssets) {
+    audio_assets.play_effect(commands, Sound::PlayerDestroyed, position);
 }
@@ -0,0 +1,63 @@
+//! Pans sound effects towards where they happened on the screen.
+//!
+//! Sound effects are spatial sounds placed where their event happened, heard by a
+//! `SpatialListener` on the camera whose ears sit on the left and right edges of the arena.
+//! Only the x offset from the camera is used, so a sound at the edge of the screen is
+//! louder in that ear, and a little quieter overall, while sounds above or below the
+//! camera aren't panned.
+
+use bevy::{
+    audio::{AudioSinkPlayback, DefaultSpatialScale, SpatialScale},
+    ecs::query::QueryData,
+    prelude::*,
+};
+
+use crate::arena::Arena;
+
+/// The sink of a playing sound. Spatial sounds play through a `SpatialAudioSink` instead
+/// of an `AudioSink`.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct SoundSink {
+    sink: Option<&'static mut AudioSink>,
+    spatial_sink: Option<&'static mut SpatialAudioSink>,
+}
+
+impl SoundSinkItem<'_> {
+    /// Returns the sound's sink, or `None` if it hasn't started playing.
+    pub fn playback(&mut self) -> Option<&mut dyn AudioSinkPlayback> {
+        match (&mut self.sink, &mut self.spatial_sink) {
+            (Some(sink), _) => Some(&mut **sink),
+            (None, Some(sink)) => Some(&mut **sink),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Keeps the `SpatialListener` on the camera spanning the arena.
+///
+/// The positions of the sounds and the listener's ears are scaled so that the ears are one
+/// unit away from the centre, as the volume in each ear drops with the square of its
+/// distance to the sound once the sound is more than a unit away.
+///
+/// # Arguments
+/// * `arena`: The `Arena` resource with the size of the screen.
+/// * `spatial_scale`: The `DefaultSpatialScale` resource to scale the sound positions by.
+/// * `listeners`: A query that retrieves every `SpatialListener`.
+pub fn sync_spatial_audio(
+    arena: Res<Arena>,
+    mut spatial_scale: ResMut<DefaultSpatialScale>,
+    mut listeners: Query<&mut SpatialListener>,
+) {
+    let added = listeners.iter_mut().any(|listener| listener.is_added());
+    if !arena.is_changed() && !added {
+        return;
+    }
+
+    // Ignore the y offset, so only the x offset pans the sound.
+    spatial_scale.0 = SpatialScale(Vec3::new(1.0 / arena.half_size().x, 0.0, 0.0));
+
+    for mut listener in listeners.iter_mut() {
+        *listener = SpatialListener::new(arena.width());
+    }
+}
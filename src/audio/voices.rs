@@ -0,0 +1,81 @@
+//! Keeps repeated sound effects from piling up and sounding robotic.
+//!
+//! Every sound effect is a voice of its [`Sound`]. Each sound only plays so many voices at
+//! once, and starting another one stops the oldest. Every voice also plays at a slightly
+//! different pitch and volume.
+
+use std::collections::VecDeque;
+
+use bevy::{audio::Volume, platform::collections::HashMap, prelude::*};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{AudioChannel, Sound};
+
+/// How far the pitch of a voice can be from the sound's own pitch, as a fraction of it.
+pub const PITCH_VARIATION: f32 = 0.05;
+
+/// How much quieter than the sound a voice can be, as a fraction of its volume.
+pub const VOLUME_VARIATION: f32 = 0.15;
+
+/// A playing sound effect.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Voice(pub Sound);
+
+/// The voices of every sound effect, from the oldest to the newest.
+#[derive(Resource, Debug, Default)]
+pub struct Voices(HashMap<Sound, VecDeque<Entity>>);
+
+impl Voices {
+    /// Returns how many voices of a sound are playing.
+    pub fn count(&self, sound: Sound) -> usize {
+        self.0.get(&sound).map_or(0, VecDeque::len)
+    }
+}
+
+/// Varies the voices. It isn't part of the `GameRng`, so it doesn't change how runs play out.
+#[derive(Resource, Debug)]
+pub struct VoiceRng(pub ChaCha8Rng);
+
+impl Default for VoiceRng {
+    fn default() -> Self {
+        Self(ChaCha8Rng::seed_from_u64(rand::random()))
+    }
+}
+
+/// Varies new voices, and stops the oldest voices of any sound that is over its limit.
+///
+/// This has to run before the `AudioSettings` are applied to the new sounds.
+///
+/// # Arguments
+/// * `commands`: The `Commands` to stop voices.
+/// * `voices`: The `Voices` resource with the playing voices.
+/// * `rng`: The `VoiceRng` resource to vary the new voices.
+/// * `new_voices`: A query that retrieves every new voice, and how it is played.
+/// * `players`: A query that checks which voices are still playing.
+pub fn manage_voices(
+    mut commands: Commands,
+    mut voices: ResMut<Voices>,
+    mut rng: ResMut<VoiceRng>,
+    mut new_voices: Query<(Entity, &Voice, &mut PlaybackSettings, &mut AudioChannel), Added<Voice>>,
+    players: Query<(), With<AudioPlayer>>,
+) {
+    // Voices are despawned once they finish.
+    for playing in voices.0.values_mut() {
+        playing.retain(|entity| players.contains(*entity));
+    }
+
+    for (entity, Voice(sound), mut playback, mut channel) in new_voices.iter_mut() {
+        playback.speed *= 1.0 + rng.0.random_range(-PITCH_VARIATION..=PITCH_VARIATION);
+        channel.volume *= Volume::Linear(1.0 - rng.0.random_range(0.0..=VOLUME_VARIATION));
+
+        let playing = voices.0.entry(*sound).or_default();
+        playing.push_back(entity);
+
+        while playing.len() > sound.max_voices() {
+            if let Some(oldest) = playing.pop_front() {
+                commands.entity(oldest).despawn();
+            }
+        }
+    }
+}
@@ -2,40 +2,65 @@
 
 use bevy::{asset::RenderAssetUsages, prelude::*, render::mesh::PrimitiveTopology};
 
-/// A bullet fired from the `PlayerShip`.
+use crate::{
+    arena::WrapGhosts, collision::CollisionPolygon, interpolation::TransformInterpolation,
+    physics::Velocity,
+};
+
+/// The points that make up the bullet, drawn as a line strip.
+pub const BULLET_POINTS: &[Vec3] = &[Vec3::new(0.0, 0.0, 2.0), Vec3::new(0.0, 5.0, 2.0)];
+
+/// How fast a bullet flies away from the ship that fired it.
+pub const BULLET_SPEED: f32 = 55.0;
+
+/// How long a bullet lasts in a wrapping arena, where it never leaves the screen.
+pub const BULLET_LIFETIME: f32 = 8.0;
+
+/// A bullet fired from the `PlayerShip`. It flies with its `Velocity`.
 #[derive(Component)]
 pub struct Bullet {
-    pub speed: f32,
+    /// How long the bullet has left in a wrapping arena.
+    pub lifetime: Timer,
+    /// Where the bullet was at the start of the tick, to sweep its collisions from.
+    pub last_position: Vec2,
 }
 
 impl Bullet {
-    /// Creates a new bullet with the specified speed.
+    /// Creates a new bullet fired from a ship, on top of the ship's own velocity.
     ///
     /// # Arguments
     /// * `commands`: The `Commands` resource to spawn the bullet entity.
-    /// * `transform`: The `Transform` of the bullet, which determines its position and rotation.
-    /// * `speed`: The speed of the bullet.
+    /// * `transform`: The `Transform` of the ship firing the bullet.
+    /// * `ship_velocity`: The `Velocity` of the ship, which the bullet inherits.
     /// * `bullet_config`: The `BulletConfig` resource that holds the bullet's mesh
     pub fn spawn_bullet(
         commands: &mut Commands,
         mut transform: Transform,
-        speed: f32,
+        ship_velocity: Velocity,
         bullet_config: &Res<BulletConfig>,
     ) {
-        // Modify the transform to start the bullet at the ship's nose
-        let angle = transform.rotation.to_euler(EulerRot::ZXY).0;
+        let facing = transform.up().truncate();
 
-        // Move the bullet 5 units forward in the direction of the ship's rotation
-        transform.translation.x += -angle.sin() * 5.0;
-        transform.translation.y += angle.cos() * 5.0;
+        // Move the bullet 5 units forward to start it at the ship's nose
+        transform.translation += (facing * 5.0).extend(0.0);
+
+        let velocity = ship_velocity.0 + facing * BULLET_SPEED;
+
+        // Point the bullet the way it flies
+        transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_to(velocity));
 
         commands.spawn((
             Mesh2d(bullet_config.mesh.clone()),
             MeshMaterial2d(bullet_config.material.clone()),
             transform,
+            CollisionPolygon::new(BULLET_POINTS),
+            TransformInterpolation::default(),
+            Velocity(velocity),
             Bullet {
-                speed: 55.0 + speed,
+                lifetime: Timer::from_seconds(BULLET_LIFETIME, TimerMode::Once),
+                last_position: transform.translation.truncate(),
             },
+            WrapGhosts { radius: 5.0 },
         ));
     }
 }
@@ -62,10 +87,7 @@ pub fn setup_bullet(
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     let bullet_mesh = Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::all())
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_POSITION,
-            vec![Vec3::new(0.0, 0.0, 2.0), Vec3::new(0.0, 5.0, 2.0)],
-        );
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, BULLET_POINTS.to_vec());
 
     let bullet_material = materials.add(ColorMaterial::from(Color::LinearRgba(LinearRgba::new(
         1.0, 0.2, 0.2, 1.0,
@@ -88,3 +110,40 @@ impl BulletConfig {
         Self { mesh, material }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn bullets_fly_on_top_of_the_ship_velocity() {
+        let mut world = World::new();
+        world.insert_resource(BulletConfig::new(Handle::default(), Handle::default()));
+
+        // A ship facing right while it drifts up.
+        let ship = Transform::from_rotation(Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2));
+        let ship_velocity = Velocity(Vec2::new(0.0, 30.0));
+
+        world
+            .run_system_once(
+                move |mut commands: Commands, bullet_config: Res<BulletConfig>| {
+                    Bullet::spawn_bullet(&mut commands, ship, ship_velocity, &bullet_config);
+                },
+            )
+            .unwrap();
+
+        let (transform, velocity) = world
+            .query_filtered::<(&Transform, &Velocity), With<Bullet>>()
+            .single(&world)
+            .unwrap();
+
+        let expected = Vec2::new(BULLET_SPEED, 30.0);
+        assert!(velocity.0.abs_diff_eq(expected, 1e-4), "{}", velocity.0);
+
+        // The bullet points the way it flies, not the way the ship faces.
+        let facing = transform.up().truncate();
+        assert!(facing.abs_diff_eq(expected.normalize(), 1e-4), "{facing}");
+    }
+}
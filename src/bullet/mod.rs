@@ -7,7 +7,7 @@ pub use data::*;
 pub mod systems;
 pub use systems::*;
 
-use crate::GameState;
+use crate::{InGame, collision::rebuild_asteroid_grid, ship::player_input_and_movement};
 use bevy::prelude::*;
 
 pub struct BulletPlugin;
@@ -16,10 +16,19 @@ impl Plugin for BulletPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(PostStartup, setup_bullet);
         app.add_systems(
-            Update,
-            (move_bullets, check_bullet_bounds, check_bullet_collisions)
-                .run_if(in_state(GameState::Game)),
+            FixedUpdate,
+            (move_bullets, check_bullet_bounds)
+                .chain()
+                .after(player_input_and_movement)
+                .run_if(in_state(InGame)),
         );
-        app.add_systems(OnExit(GameState::Game), despawn_bullets);
+        app.add_systems(
+            FixedUpdate,
+            (check_bullet_collisions, resolve_bullet_hits)
+                .chain()
+                .after(rebuild_asteroid_grid)
+                .run_if(in_state(InGame)),
+        );
+        app.add_systems(OnExit(InGame), despawn_bullets);
     }
 }
@@ -1,56 +1,78 @@
 //! Systems for managing bullets in the game.
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
 
 use crate::{
+    arena::{Arena, wrap_transform},
     asteroid::{Asteroid, AsteroidSize},
-    audio::asteroid::destroy_asteroid,
+    audio::{AudioAssets, asteroid::destroy_asteroid},
+    collision::{AsteroidGrid, BulletHitAsteroid, CollisionPolygon, ResolvedCollisions},
     explosion::{ExplosionConfig, create_explosion},
-    lines_intersect, mesh_and_transform_to_points,
+    interpolation::TransformInterpolation,
+    lines_intersect,
+    physics::Velocity,
+    point_in_polygon,
+    rng::GameRng,
+    stats::RunStats,
     ui::ScoreEvent,
 };
+use rand::Rng;
 
 use super::Bullet;
 
-/// Handles the movement of bullets based on their speed and the time elapsed since the last frame.
+/// Moves the bullets along their velocity.
 ///
 /// # Arguments
 /// * `time`: The `Time` resource to calculate the movement delta.
-/// * `query`: A query that retrieves every `Bullet` and its `Transform`.
-pub fn move_bullets(time: Res<Time>, mut query: Query<(&mut Transform, &Bullet)>) {
-    for (mut transform, bullet) in query.iter_mut() {
-        let angle = transform.rotation.to_euler(EulerRot::ZXY).0;
+/// * `query`: A query that retrieves every `Bullet`, its `Velocity` and its `Transform`.
+pub fn move_bullets(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity, &mut Bullet)>) {
+    for (mut transform, velocity, mut bullet) in query.iter_mut() {
+        bullet.last_position = transform.translation.truncate();
 
-        transform.translation.x += -angle.sin() * bullet.speed * time.delta_secs();
-        transform.translation.y += angle.cos() * bullet.speed * time.delta_secs();
+        velocity.advance(&mut transform, time.delta_secs());
     }
 }
 
-/// Checks if bullets are within the bounds of the game window and despawns them if they are not.
+/// Checks if bullets are within the bounds of the arena and despawns them if they are not.
+///
+/// In a wrapping arena bullets wrap around instead, and are despawned once they expire.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to despawn bullets that are out of bounds.
 /// * `query`: A query that retrieves every `Bullet` and its `Transform`.
-/// * `window`: A query that retrieves the primary window to get its size.
+/// * `arena`: The `Arena` resource to get the size of the playfield.
+/// * `time`: The `Time` resource to age the bullets.
 pub fn check_bullet_bounds(
     mut commands: Commands,
-    mut query: Query<(Entity, &Bullet, &Transform)>,
-    window: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<(
+        Entity,
+        &mut Bullet,
+        &mut Transform,
+        Option<&mut TransformInterpolation>,
+    )>,
+    arena: Res<Arena>,
+    time: Res<Time>,
 ) {
-    // Get the window size
-    let window = window.single().unwrap();
+    let arena_size = arena.size;
 
-    let window_size = Vec2::new(window.width(), window.height());
+    for (entity, mut bullet, mut transform, interpolation) in query.iter_mut() {
+        if arena.wraps() {
+            if bullet.lifetime.tick(time.delta()).finished() {
+                commands.entity(entity).despawn();
+            } else {
+                wrap_transform(&arena, &mut transform, interpolation);
+            }
+            continue;
+        }
 
-    for (entity, _, transform) in query.iter_mut() {
         // Get the asteroid width
         let radius = 1.75;
 
         // Check if the asteroid is out of bounds
-        if transform.translation.x + radius < -window_size.x / 2.0
-            || transform.translation.x - radius > window_size.x / 2.0
-            || transform.translation.y + radius < -window_size.y / 2.0
-            || transform.translation.y - radius > window_size.y / 2.0
+        if transform.translation.x + radius < -arena_size.x / 2.0
+            || transform.translation.x - radius > arena_size.x / 2.0
+            || transform.translation.y + radius < -arena_size.y / 2.0
+            || transform.translation.y - radius > arena_size.y / 2.0
         {
             // Remove the asteroid
             commands.entity(entity).despawn();
@@ -58,140 +80,313 @@ pub fn check_bullet_bounds(
     }
 }
 
-/// Checks for collisions between bullets and asteroids, and handles the destruction of both.
+/// Checks for collisions between bullets and asteroids, and sends a `BulletHitAsteroid`
+/// event for each bullet that hits one.
+///
+/// Each bullet is swept from where it was at the start of the tick to where its nose is
+/// now, so a fast bullet can't pass through an asteroid between two ticks. A bullet that
+/// ends up entirely inside an asteroid hits it as well.
+///
+/// A bullet hits at most one asteroid per tick, the first one along its sweep. The hits
+/// are resolved by `resolve_bullet_hits`.
+///
+/// # Arguments
+/// * `asteroids`: A query that retrieves every `Asteroid`'s `Transform` and `CollisionPolygon`.
+/// * `bullets`: A query that retrieves every `Bullet` and its `CollisionPolygon`.
+/// * `arena`: The `Arena` resource to find the nearest copy of each asteroid in a wrapping arena.
+/// * `grid`: The `AsteroidGrid` resource to find the asteroids near each bullet.
+/// * `hits`: The `EventWriter` to send the hits to.
+pub fn check_bullet_collisions(
+    asteroids: Query<(&Transform, &CollisionPolygon), With<Asteroid>>,
+    bullets: Query<(Entity, &Bullet, &CollisionPolygon)>,
+    arena: Res<Arena>,
+    grid: Res<AsteroidGrid>,
+    mut hits: EventWriter<BulletHitAsteroid>,
+) {
+    for (bullet_entity, bullet, bullet_polygon) in bullets.iter() {
+        // Get the bullet's nose
+        let Some(&nose) = bullet_polygon.world().last() else {
+            continue;
+        };
+
+        // The bullet may have wrapped around the arena since the start of the tick.
+        let sweep_start =
+            bullet.last_position + arena.nearest_copy_offset(nose, bullet.last_position);
+        let sweep_centre = sweep_start.midpoint(nose);
+
+        // Only the asteroids near the sweep can be hit - We add a small buffer to the distance
+        // to account for the bullet's size and ensure it hits the asteroid.
+        // This is a bit of a hack, but it works well enough. :\
+        let nearby = grid.query(sweep_centre, sweep_start.distance(nose) / 2.0 + 3.5);
+
+        let hit = nearby
+            .filter_map(|entry| {
+                let (asteroid_transform, asteroid_polygon) = asteroids.get(entry.entity).ok()?;
+
+                // In a wrapping arena, collide with whichever copy of the asteroid is closest
+                let offset = arena
+                    .nearest_copy_offset(sweep_centre, asteroid_transform.translation.truncate());
+
+                // Find where the sweep first crosses the asteroid's outline
+                let crossing = asteroid_polygon
+                    .edges()
+                    .filter_map(|(line_start, line_end)| {
+                        lines_intersect(line_start + offset, line_end + offset, sweep_start, nose)
+                    })
+                    .min_by(|a, b| {
+                        sweep_start
+                            .distance_squared(*a)
+                            .total_cmp(&sweep_start.distance_squared(*b))
+                    });
+
+                // A bullet that is entirely inside the asteroid doesn't cross its outline
+                let point = crossing.or_else(|| {
+                    point_in_polygon(nose - offset, asteroid_polygon.world()).then_some(nose)
+                })?;
+
+                Some(BulletHitAsteroid {
+                    bullet: bullet_entity,
+                    asteroid: entry.entity,
+                    point,
+                })
+            })
+            .min_by(|a, b| {
+                sweep_start
+                    .distance_squared(a.point)
+                    .total_cmp(&sweep_start.distance_squared(b.point))
+            });
+
+        if let Some(hit) = hit {
+            hits.write(hit);
+        }
+    }
+}
+
+/// Destroys the bullets and asteroids of this tick's `BulletHitAsteroid` events, and splits
+/// the asteroids into smaller ones.
+///
+/// Bullets and asteroids that were already consumed by another collision this tick are
+/// skipped, so every hit is only scored once.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to despawn bullets and asteroids.
-/// * `asteroids`: A query that retrieves every `Asteroid` and its `Transform`.
-/// * `bullets`: A query that retrieves every `Bullet` and its `Transform`.
-/// * `asset_server`: The `AssetServer` resource to play sound effects.
-/// * `audio`: The `AudioChannel<ExplosionChannel>` resource to play the sound effects.
-/// * `meshes`: The `Assets<Mesh>` resource to get the mesh of the asteroids and bullets.
-/// * `materials`: The `Assets<ColorMaterial>` resource to get the material of the bullets.
+/// * `hits`: The `EventReader` to read the hits from.
+/// * `asteroids`: A query that retrieves every `Asteroid`, its `Transform` and its `Velocity`.
+/// * `resolved`: The `ResolvedCollisions` resource to consume each entity at most once.
+/// * `audio_assets`: The `AudioAssets` resource to play sound effects.
+/// * `meshes`: The `Assets<Mesh>` resource to create the meshes of new asteroids.
+/// * `materials`: The `Assets<ColorMaterial>` resource to create the materials of new asteroids.
 /// * `explosion_config`: The `ExplosionConfig` resource to create explosions.
-/// * `time`: The `Time` resource to determine the frequency of asteroid spawning.
-pub fn check_bullet_collisions(
+/// * `time`: The `Time` resource to start the explosions.
+/// * `rng`: The `GameRng` resource to place the children of destroyed asteroids.
+/// * `stats`: The `RunStats` resource to count the hits and destroyed asteroids.
+pub fn resolve_bullet_hits(
     mut commands: Commands,
-    asteroids: Query<(Entity, &Asteroid, &Transform, &Mesh2d)>,
-    bullets: Query<(Entity, &Bullet, &Transform, &Mesh2d)>,
-    asset_server: Res<AssetServer>,
+    mut hits: EventReader<BulletHitAsteroid>,
+    asteroids: Query<(&Asteroid, &Transform, &Velocity)>,
+    mut resolved: ResMut<ResolvedCollisions>,
+    audio_assets: Res<AudioAssets>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     explosion_config: Res<ExplosionConfig>,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+    mut stats: ResMut<RunStats>,
 ) {
-    for (bullet_entity, _, bullet_transform, bullet_mesh) in bullets.iter() {
-        for (asteroid_entity, asteroid, asteroid_transform, asteroid_mesh) in asteroids.iter() {
-            let asteroid_diameter = asteroid.size.diameter();
-
-            let distance = bullet_transform
-                .translation
-                .distance(asteroid_transform.translation);
-
-            // Check if the bullet is colliding with the asteroid - We add a small buffer to the distance
-            // to account for the bullet's size and ensure it hits the asteroid.
-            // This is a bit of a hack, but it works well enough. :\
-            if distance < asteroid_diameter + 3.5 {
-                // Get the asteroid's points
-                let asteroid_mesh = meshes.get(&asteroid_mesh.0).unwrap();
-
-                let asteroid_points =
-                    mesh_and_transform_to_points(asteroid_mesh, asteroid_transform);
-
-                // Get the bullet's points
-                let bullet_mesh = meshes.get(&bullet_mesh.0).unwrap();
-
-                let bullet_points = mesh_and_transform_to_points(bullet_mesh, bullet_transform);
-
-                let bullet_start = bullet_points[0];
-                let bullet_end = bullet_points[1];
-
-                // Check if any of the lines of the bullet intersect with the asteroid
-                asteroid_points.windows(2).for_each(|p| {
-                    let line_start = Vec2::new(p[0][0], p[0][1]);
-                    let line_end = Vec2::new(p[1][0], p[1][1]);
-
-                    // Check if the line intersects with the asteroid
-                    if let Some(p) = lines_intersect(line_start, line_end, bullet_start, bullet_end)
-                    {
-                        // Create the transform for the explosion
-                        let point_of_contact = Transform::from_translation(p.extend(-1.0));
-
-                        // Blow up the asteroid
-                        commands.entity(asteroid_entity).despawn();
-
-                        // Blow up the bullet
-                        commands.entity(bullet_entity).despawn();
-
-                        // Spawn an explosion
-                        create_explosion(
-                            &mut commands,
-                            point_of_contact,
-                            &explosion_config,
-                            &time,
-                            false,
-                        );
-
-                        // Play the asteroid destruction sound
-                        destroy_asteroid(&mut commands, asteroid.size, &asset_server);
-
-                        // Create a score event
-                        commands.send_event(ScoreEvent(1));
-
-                        // Check if we need to make children
-                        if asteroid.size != AsteroidSize::Small {
-                            let child_size = match asteroid.size {
-                                AsteroidSize::Medium => AsteroidSize::Small,
-                                AsteroidSize::Large => AsteroidSize::Medium,
-                                _ => unreachable!(),
-                            };
-
-                            // Spawn two smaller asteroids
-                            for _ in 0..2 {
-                                // Pick a random spot in the asteroid's diameter
-                                let diameter = asteroid.size.diameter();
-
-                                // Generate a random point within the asteroid's diameter
-                                let r = diameter * rand::random_range(0.0f32..1.0).sqrt();
-
-                                // Generate a random angle
-                                // This is done by picking a random angle between 0 and 2 * PI
-                                let theta =
-                                    rand::random_range(0.0f32..1.0) * 2.0 * std::f32::consts::PI;
-
-                                // Calculate the x and y coordinates of the point
-                                // using polar coordinates
-                                let x = r * theta.cos();
-                                let y = r * theta.sin();
-
-                                let location = Vec3::new(
-                                    asteroid_transform.translation.x + x,
-                                    asteroid_transform.translation.y + y,
-                                    0.0,
-                                );
-
-                                // Pick a random direction
-                                let direction = Vec2::new(
-                                    rand::random_range(-1.0f32..1.0),
-                                    rand::random_range(-1.0f32..1.0),
-                                )
-                                .normalize()
-                                    + asteroid.direction;
-
-                                // Spawn the new asteroid
-                                Asteroid::spawn_new(
-                                    child_size,
-                                    location,
-                                    direction,
-                                    &mut commands,
-                                    &mut meshes,
-                                    &mut materials,
-                                );
-                            }
-                        }
-                    }
-                });
+    let GameRng {
+        splitting, shapes, ..
+    } = &mut *rng;
+
+    for hit in hits.read() {
+        let Ok((asteroid, asteroid_transform, asteroid_velocity)) = asteroids.get(hit.asteroid)
+        else {
+            continue;
+        };
+
+        if !resolved.consume(hit.bullet, hit.asteroid) {
+            continue;
+        }
+
+        // Create the transform for the explosion
+        let point_of_contact = Transform::from_translation(hit.point.extend(-1.0));
+
+        // Blow up the asteroid
+        commands.entity(hit.asteroid).despawn();
+        stats.shots_hit += 1;
+        stats.record_destroyed(asteroid.size);
+
+        // Blow up the bullet
+        commands.entity(hit.bullet).despawn();
+
+        // Spawn an explosion
+        create_explosion(
+            &mut commands,
+            point_of_contact,
+            &explosion_config,
+            &time,
+            false,
+        );
+
+        // Play the asteroid destruction sound
+        destroy_asteroid(&mut commands, asteroid.size, hit.point, &audio_assets);
+
+        // Create a score event
+        commands.send_event(ScoreEvent(1));
+
+        // Check if we need to make children
+        if asteroid.size != AsteroidSize::Small {
+            let child_size = match asteroid.size {
+                AsteroidSize::Medium => AsteroidSize::Small,
+                AsteroidSize::Large => AsteroidSize::Medium,
+                _ => unreachable!(),
+            };
+
+            // Spawn two smaller asteroids
+            for _ in 0..2 {
+                // Pick a random spot in the asteroid's diameter
+                let diameter = asteroid.size.diameter();
+
+                // Generate a random point within the asteroid's diameter
+                let r = diameter * splitting.random_range(0.0f32..1.0).sqrt();
+
+                // Generate a random angle
+                // This is done by picking a random angle between 0 and 2 * PI
+                let theta = splitting.random_range(0.0f32..1.0) * 2.0 * std::f32::consts::PI;
+
+                // Calculate the x and y coordinates of the point
+                // using polar coordinates
+                let x = r * theta.cos();
+                let y = r * theta.sin();
+
+                let location = Vec3::new(
+                    asteroid_transform.translation.x + x,
+                    asteroid_transform.translation.y + y,
+                    0.0,
+                );
+
+                // Pick a random direction, on top of the asteroid's own velocity
+                let velocity = Vec2::new(
+                    splitting.random_range(-1.0f32..1.0),
+                    splitting.random_range(-1.0f32..1.0),
+                )
+                .normalize()
+                    + asteroid_velocity.0;
+
+                // Spawn the new asteroid
+                Asteroid::spawn_new(
+                    child_size,
+                    location,
+                    velocity,
+                    shapes,
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::bullet::BULLET_POINTS;
+
+    /// Creates a world with a small, round asteroid at the origin.
+    fn world_with_asteroid() -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(Arena::default());
+        world.init_resource::<Events<BulletHitAsteroid>>();
+
+        // The smallest a small asteroid can be.
+        let radius = 8.0;
+        let outline = (0..=12)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 12.0;
+                Vec2::from_angle(angle).extend(0.0) * radius
+            })
+            .collect::<Vec<_>>();
+
+        let transform = Transform::default();
+        let mut polygon = CollisionPolygon::new(&outline);
+        polygon.update(&transform);
+
+        let size = AsteroidSize::Small;
+        let asteroid = world.spawn((Asteroid { size }, transform, polygon)).id();
+
+        let mut grid = AsteroidGrid::default();
+        grid.clear(&Arena::default());
+        grid.insert(asteroid, Vec2::ZERO, size.diameter());
+        world.insert_resource(grid);
+
+        (world, asteroid)
+    }
+
+    /// Spawns a bullet flying right that moved from `from` on the last tick, and whose
+    /// nose is now at `nose`.
+    fn spawn_bullet(world: &mut World, from: Vec2, nose: Vec2) -> Entity {
+        let mut transform = Transform::from_translation((nose - Vec2::X * 5.0).extend(0.0));
+        transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_to(Vec2::X));
+
+        let mut polygon = CollisionPolygon::new(BULLET_POINTS);
+        polygon.update(&transform);
+
+        world
+            .spawn((
+                Bullet {
+                    lifetime: Timer::from_seconds(1.0, TimerMode::Once),
+                    last_position: from,
+                },
+                transform,
+                polygon,
+            ))
+            .id()
+    }
+
+    fn hits(world: &mut World) -> Vec<BulletHitAsteroid> {
+        world.run_system_once(check_bullet_collisions).unwrap();
+
+        let events = world.resource::<Events<BulletHitAsteroid>>();
+        events.get_cursor().read(events).copied().collect()
+    }
+
+    #[test]
+    fn a_fast_bullet_hits_an_asteroid_it_passed_through() {
+        let (mut world, asteroid) = world_with_asteroid();
+
+        // The bullet starts and ends the tick well clear of either side of the asteroid.
+        let bullet = spawn_bullet(&mut world, Vec2::new(-30.0, 0.0), Vec2::new(30.0, 0.0));
+
+        let hits = hits(&mut world);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].bullet, bullet);
+        assert_eq!(hits[0].asteroid, asteroid);
+
+        // It hits the near side of the asteroid, where it first crossed it.
+        assert!((hits[0].point - Vec2::new(-8.0, 0.0)).length() < 0.5);
+    }
+
+    #[test]
+    fn a_bullet_that_only_passes_by_misses() {
+        let (mut world, _) = world_with_asteroid();
+
+        spawn_bullet(&mut world, Vec2::new(-30.0, 20.0), Vec2::new(30.0, 20.0));
+        // This one stops short of the asteroid.
+        spawn_bullet(&mut world, Vec2::new(-30.0, 0.0), Vec2::new(-20.0, 0.0));
+
+        assert!(hits(&mut world).is_empty());
+    }
+
+    #[test]
+    fn a_bullet_inside_an_asteroid_hits_it() {
+        let (mut world, asteroid) = world_with_asteroid();
+
+        spawn_bullet(&mut world, Vec2::new(-2.0, 0.0), Vec2::new(4.0, 0.0));
+
+        let hits = hits(&mut world);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].asteroid, asteroid);
+    }
+}
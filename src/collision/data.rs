@@ -0,0 +1,362 @@
+//! Data for the collision broadphase.
+
+use bevy::{ecs::entity::EntityHashSet, prelude::*};
+
+use crate::arena::Arena;
+
+/// The largest size of a cell in the `AsteroidGrid`, a bit larger than the largest asteroid.
+pub const GRID_CELL_SIZE: f32 = 64.0;
+
+/// The outline an entity collides with, kept alongside its mesh so that collisions
+/// don't have to read render assets.
+///
+/// The outline is a line strip in the entity's local space, like the meshes the game
+/// draws. Its world-space points are cached once per tick by `update_collision_polygons`.
+#[derive(Component, Debug, Clone, Default)]
+pub struct CollisionPolygon {
+    local: Vec<Vec2>,
+    world: Vec<Vec2>,
+}
+
+impl CollisionPolygon {
+    /// Creates a polygon from the points of a line strip mesh.
+    ///
+    /// # Arguments
+    /// * `points`: The positions of the mesh's vertices. Their depth is ignored.
+    pub fn new(points: &[Vec3]) -> Self {
+        Self {
+            local: points.iter().map(|point| point.truncate()).collect(),
+            world: Vec::new(),
+        }
+    }
+
+    /// Returns the points of the outline in the entity's local space.
+    pub fn local(&self) -> &[Vec2] {
+        &self.local
+    }
+
+    /// Returns the points of the outline in world space, as of the last update.
+    ///
+    /// This is empty until the polygon has been updated for the first time.
+    pub fn world(&self) -> &[Vec2] {
+        &self.world
+    }
+
+    /// Returns the edges of the outline in world space, as of the last update.
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.world.windows(2).map(|edge| (edge[0], edge[1]))
+    }
+
+    /// Moves the world-space outline to where the given transform places the entity.
+    pub fn update(&mut self, transform: &Transform) {
+        let affine = transform.compute_affine();
+
+        self.world.clear();
+        self.world.extend(
+            self.local
+                .iter()
+                .map(|point| affine.transform_point3(point.extend(0.0)).truncate()),
+        );
+    }
+}
+
+/// An entity stored in a `SpatialGrid`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridEntry {
+    /// The entity this entry belongs to.
+    pub entity: Entity,
+    /// The entity's position when the grid was built.
+    pub position: Vec2,
+    /// The radius of a circle that contains the entity's whole shape.
+    pub radius: f32,
+}
+
+/// A uniform grid over the arena that finds the entities near a point without
+/// checking every one of them.
+///
+/// Each entry is stored in the cell that contains its centre, and a query looks at
+/// every cell within reach of the largest entry. Positions outside a bounded arena
+/// are kept in the cells along its edge, and a wrapping arena wraps its cells around
+/// so that queries near an edge also find the entries on the opposite side.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    /// The largest size a cell can have.
+    max_cell_size: f32,
+    /// The size of each cell, which fits a whole number of cells into the arena.
+    cell_size: Vec2,
+    /// The number of cells across and down the arena.
+    cells_across: IVec2,
+    arena: Arena,
+    cells: Vec<Vec<GridEntry>>,
+    /// The largest radius of any entry, so queries know how far to look.
+    max_radius: f32,
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid with cells of at most the given size.
+    pub fn new(max_cell_size: f32) -> Self {
+        Self {
+            max_cell_size,
+            cell_size: Vec2::splat(max_cell_size),
+            cells_across: IVec2::ZERO,
+            arena: Arena::default(),
+            cells: Vec::new(),
+            max_radius: 0.0,
+        }
+    }
+
+    /// Removes every entry and resizes the grid to cover the given arena.
+    pub fn clear(&mut self, arena: &Arena) {
+        self.arena = *arena;
+        self.cells_across = (arena.size / self.max_cell_size)
+            .ceil()
+            .as_ivec2()
+            .max(IVec2::ONE);
+        // The cells have to line up with the edges for a wrapping arena to wrap them around.
+        self.cell_size = arena.size / self.cells_across.as_vec2();
+        self.max_radius = 0.0;
+
+        let cell_count = (self.cells_across.x * self.cells_across.y) as usize;
+        self.cells.resize_with(cell_count, Vec::new);
+        self.cells.iter_mut().for_each(Vec::clear);
+    }
+
+    /// Adds an entity with the given position and radius to the grid.
+    pub fn insert(&mut self, entity: Entity, position: Vec2, radius: f32) {
+        let cell = self.cell_index(self.cell_of(position));
+
+        self.cells[cell].push(GridEntry {
+            entity,
+            position,
+            radius,
+        });
+        self.max_radius = self.max_radius.max(radius);
+    }
+
+    /// Returns the number of entries in the grid.
+    pub fn len(&self) -> usize {
+        self.cells.iter().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the grid has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(Vec::is_empty)
+    }
+
+    /// Returns every entry whose circle overlaps the circle at `position` with the given `radius`.
+    ///
+    /// In a wrapping arena this also finds entries whose copy on the other side of an
+    /// edge overlaps the circle.
+    pub fn query(&self, position: Vec2, radius: f32) -> impl Iterator<Item = &GridEntry> + '_ {
+        let reach = Vec2::splat(radius + self.max_radius);
+        let min = self.cell_of(position - reach);
+        let max = self.cell_of(position + reach);
+
+        let columns = self.cell_range(min.x, max.x, self.cells_across.x);
+        let rows = self.cell_range(min.y, max.y, self.cells_across.y);
+
+        rows.flat_map(move |y| columns.clone().map(move |x| IVec2::new(x, y)))
+            .flat_map(move |cell| self.cells[self.cell_index(cell)].iter())
+            .filter(move |entry| {
+                let offset = self.arena.nearest_copy_offset(position, entry.position);
+
+                position.distance(entry.position + offset) < radius + entry.radius
+            })
+    }
+
+    /// Returns the cell that contains a point, which may lie outside of the grid.
+    fn cell_of(&self, point: Vec2) -> IVec2 {
+        ((point + self.arena.half_size()) / self.cell_size)
+            .floor()
+            .as_ivec2()
+    }
+
+    /// Returns the index of a cell, wrapping or clamping it into the grid.
+    fn cell_index(&self, cell: IVec2) -> usize {
+        let cell = if self.arena.wraps() {
+            cell.rem_euclid(self.cells_across)
+        } else {
+            cell.clamp(IVec2::ZERO, self.cells_across - 1)
+        };
+
+        (cell.y * self.cells_across.x + cell.x) as usize
+    }
+
+    /// Returns the cells from `min` to `max` along an axis with `count` cells, visiting
+    /// each cell of the grid at most once.
+    fn cell_range(&self, min: i32, max: i32, count: i32) -> std::ops::Range<i32> {
+        if self.arena.wraps() {
+            // Out of range cells are wrapped by `cell_index`.
+            min..max.min(min + count - 1) + 1
+        } else {
+            min.clamp(0, count - 1)..max.clamp(0, count - 1) + 1
+        }
+    }
+}
+
+/// The broadphase for collisions with asteroids, rebuilt from every asteroid once per tick.
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct AsteroidGrid(pub SpatialGrid);
+
+impl Default for AsteroidGrid {
+    fn default() -> Self {
+        Self(SpatialGrid::new(GRID_CELL_SIZE))
+    }
+}
+
+/// Sent when a bullet hits an asteroid.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BulletHitAsteroid {
+    pub bullet: Entity,
+    pub asteroid: Entity,
+    /// Where the bullet hit the asteroid.
+    pub point: Vec2,
+}
+
+/// Sent when the player ship hits an asteroid.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ShipHitAsteroid {
+    pub ship: Entity,
+    pub asteroid: Entity,
+    /// Where the ship hit the asteroid.
+    pub point: Vec2,
+}
+
+/// The entities that have already been consumed by a collision this tick.
+///
+/// Every collision is resolved against this set, so e.g. two bullets that hit the same
+/// asteroid on the same tick only destroy it once.
+#[derive(Resource, Debug, Default)]
+pub struct ResolvedCollisions(EntityHashSet);
+
+impl ResolvedCollisions {
+    /// Consumes both entities of a collision.
+    ///
+    /// Returns `false` without consuming anything if either entity was already consumed this tick.
+    pub fn consume(&mut self, first: Entity, second: Entity) -> bool {
+        if self.0.contains(&first) || self.0.contains(&second) {
+            return false;
+        }
+
+        self.0.insert(first);
+        self.0.insert(second);
+        true
+    }
+
+    /// Forgets the entities consumed on the last tick.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::EdgeMode;
+
+    /// Builds a grid over the arena with an entry for every circle, in order.
+    fn grid_with(arena: &Arena, circles: &[(Vec2, f32)]) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(GRID_CELL_SIZE);
+        grid.clear(arena);
+
+        for (index, &(position, radius)) in circles.iter().enumerate() {
+            grid.insert(Entity::from_raw(index as u32), position, radius);
+        }
+
+        grid
+    }
+
+    /// Returns the indices of the circles a query finds, in order.
+    fn found(grid: &SpatialGrid, position: Vec2, radius: f32) -> Vec<u32> {
+        let mut found = grid
+            .query(position, radius)
+            .map(|entry| entry.entity.index())
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the indices of the circles that overlap the query, by checking every one.
+    fn all_pairs(arena: &Arena, circles: &[(Vec2, f32)], position: Vec2, radius: f32) -> Vec<u32> {
+        (0..circles.len() as u32)
+            .filter(|&index| {
+                let (circle, circle_radius) = circles[index as usize];
+                let offset = arena.nearest_copy_offset(position, circle);
+                position.distance(circle + offset) < radius + circle_radius
+            })
+            .collect()
+    }
+
+    /// Circles along every edge and in every corner of the arena.
+    fn edge_circles(arena: &Arena) -> Vec<(Vec2, f32)> {
+        let half_size = arena.half_size();
+        let mut circles = Vec::new();
+
+        for x in [-1.0, -0.5, 0.0, 0.5, 1.0] {
+            for y in [-1.0, -0.5, 0.0, 0.5, 1.0] {
+                let inset = Vec2::splat(3.0) * Vec2::new(x, y).signum();
+                circles.push((half_size * Vec2::new(x, y) - inset, 8.0));
+            }
+        }
+
+        circles
+    }
+
+    #[test]
+    fn finds_entries_across_the_seam_of_a_wrapping_arena() {
+        let arena = Arena::new(640.0, 360.0).with_edge_mode(EdgeMode::Wrap);
+        let circles = [
+            (Vec2::new(315.0, 0.0), 10.0),
+            (Vec2::new(315.0, 175.0), 10.0),
+        ];
+        let grid = grid_with(&arena, &circles);
+
+        assert_eq!(found(&grid, Vec2::new(-315.0, 0.0), 5.0), [0]);
+        assert_eq!(found(&grid, Vec2::new(-315.0, -175.0), 5.0), [1]);
+
+        // A bounded arena doesn't reach across its edges.
+        let bounded = grid_with(&Arena::new(640.0, 360.0), &circles);
+        assert!(found(&bounded, Vec2::new(-315.0, 0.0), 5.0).is_empty());
+    }
+
+    #[test]
+    fn matches_checking_every_entry() {
+        for edge_mode in [EdgeMode::Bounded, EdgeMode::Wrap] {
+            let arena = Arena::new(640.0, 360.0).with_edge_mode(edge_mode);
+            let circles = edge_circles(&arena);
+            let grid = grid_with(&arena, &circles);
+
+            for &(position, _) in &circles {
+                for radius in [1.0, 20.0, 200.0] {
+                    assert_eq!(
+                        found(&grid, position, radius),
+                        all_pairs(&arena, &circles, position, radius),
+                        "{edge_mode:?} query at {position} with radius {radius}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn works_in_an_arena_smaller_than_a_cell() {
+        for edge_mode in [EdgeMode::Bounded, EdgeMode::Wrap] {
+            let arena = Arena::new(40.0, 30.0).with_edge_mode(edge_mode);
+            let circles = [(Vec2::new(18.0, 0.0), 2.0), (Vec2::new(0.0, 0.0), 2.0)];
+            let grid = grid_with(&arena, &circles);
+
+            assert_eq!(grid.len(), 2);
+
+            // Every entry is found once, even though every query wraps onto the same cell.
+            assert_eq!(found(&grid, Vec2::ZERO, 100.0), [0, 1]);
+
+            for position in [Vec2::new(-18.0, 0.0), Vec2::new(10.0, 14.0)] {
+                assert_eq!(
+                    found(&grid, position, 3.0),
+                    all_pairs(&arena, &circles, position, 3.0),
+                    "{edge_mode:?} query at {position}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! # Collision
+//!
+//! The collision outlines of entities, and the broadphase that every collision check
+//! looks up nearby asteroids in.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
+
+use crate::{
+    InGame, asteroid::check_asteroid_bounds, bullet::check_bullet_bounds, ship::check_ship_bounds,
+};
+use bevy::prelude::*;
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsteroidGrid>();
+        app.init_resource::<ResolvedCollisions>();
+        app.add_event::<BulletHitAsteroid>();
+        app.add_event::<ShipHitAsteroid>();
+
+        // Once everything has moved, prepare the shapes that this tick's collisions check.
+        app.add_systems(
+            FixedUpdate,
+            (
+                clear_resolved_collisions,
+                update_collision_polygons,
+                rebuild_asteroid_grid,
+            )
+                .chain()
+                .after(check_asteroid_bounds)
+                .after(check_ship_bounds)
+                .after(check_bullet_bounds)
+                .run_if(in_state(InGame)),
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Systems for the collision broadphase.
+
+use bevy::prelude::*;
+
+use crate::{arena::Arena, asteroid::Asteroid};
+
+use super::{AsteroidGrid, CollisionPolygon, ResolvedCollisions};
+
+/// Rebuilds the `AsteroidGrid` from where every asteroid is on this tick.
+///
+/// # Arguments
+/// * `grid`: The `AsteroidGrid` resource to rebuild.
+/// * `asteroids`: A query that retrieves every `Asteroid` and its `Transform`.
+/// * `arena`: The `Arena` resource to get the size of the playfield.
+pub fn rebuild_asteroid_grid(
+    mut grid: ResMut<AsteroidGrid>,
+    asteroids: Query<(Entity, &Asteroid, &Transform)>,
+    arena: Res<Arena>,
+) {
+    grid.clear(&arena);
+
+    for (entity, asteroid, transform) in asteroids.iter() {
+        grid.insert(
+            entity,
+            transform.translation.truncate(),
+            asteroid.size.diameter(),
+        );
+    }
+}
+
+/// Caches the world-space outline of every `CollisionPolygon` for this tick's collision checks.
+///
+/// # Arguments
+/// * `query`: A query that retrieves every `CollisionPolygon` and its `Transform`.
+pub fn update_collision_polygons(mut query: Query<(&Transform, &mut CollisionPolygon)>) {
+    for (transform, mut polygon) in query.iter_mut() {
+        polygon.update(transform);
+    }
+}
+
+/// Starts a new tick of collisions, in which every entity can be consumed again.
+///
+/// # Arguments
+/// * `resolved`: The `ResolvedCollisions` resource to clear.
+pub fn clear_resolved_collisions(mut resolved: ResMut<ResolvedCollisions>) {
+    resolved.clear();
+}
@@ -0,0 +1,754 @@
+//! Data for the player's actions and the keys and gamepad buttons bound to them.
+//!
+//! Gamepad buttons are fixed, and the analog sticks and triggers are read into the
+//! [`AnalogInput`] instead of an action, as is the joystick of the [`TouchControls`].
+//!
+//! The key bindings are saved as a settings file with a line per action, listing the
+//! names of the keys bound to it, and a line with the `ControlScheme`:
+//!
+//! ```text
+//! thrust KeyW ArrowUp
+//! fire Space
+//! control_scheme mouse_aim
+//! ```
+
+use std::path::PathBuf;
+
+use bevy::{input::touch::Touches, platform::collections::HashMap, prelude::*};
+
+use crate::storage::data_file;
+
+/// Something the player can do, whichever key they do it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerAction {
+    /// Accelerate the ship in the direction it is facing.
+    Thrust,
+    /// Slow the ship down, then fly backwards.
+    Brake,
+    /// Rotate the ship counter-clockwise.
+    RotateLeft,
+    /// Rotate the ship clockwise.
+    RotateRight,
+    /// Fire a bullet.
+    Fire,
+    /// Pause or resume the run.
+    Pause,
+    /// Select the option above in a menu.
+    MenuUp,
+    /// Select the option below in a menu.
+    MenuDown,
+    /// Turn the selected setting down.
+    MenuLeft,
+    /// Turn the selected setting up.
+    MenuRight,
+    /// Pick the selected option in a menu.
+    MenuConfirm,
+    /// Leave the current screen.
+    MenuBack,
+    /// Start a run from the main menu.
+    Start,
+    /// Start a new run from the game over screen.
+    Restart,
+}
+
+impl PlayerAction {
+    /// Every action.
+    pub const ALL: [PlayerAction; 14] = [
+        PlayerAction::Thrust,
+        PlayerAction::Brake,
+        PlayerAction::RotateLeft,
+        PlayerAction::RotateRight,
+        PlayerAction::Fire,
+        PlayerAction::Pause,
+        PlayerAction::MenuUp,
+        PlayerAction::MenuDown,
+        PlayerAction::MenuLeft,
+        PlayerAction::MenuRight,
+        PlayerAction::MenuConfirm,
+        PlayerAction::MenuBack,
+        PlayerAction::Start,
+        PlayerAction::Restart,
+    ];
+
+    /// The actions that can be rebound from the controls menu.
+    ///
+    /// The menu actions are left out, so that the menus can't be made unusable.
+    pub const REBINDABLE: [PlayerAction; 8] = [
+        PlayerAction::Thrust,
+        PlayerAction::Brake,
+        PlayerAction::RotateLeft,
+        PlayerAction::RotateRight,
+        PlayerAction::Fire,
+        PlayerAction::Pause,
+        PlayerAction::Start,
+        PlayerAction::Restart,
+    ];
+
+    /// The rebindable actions read while flying the ship, which can't share a key.
+    ///
+    /// Start and restart are only read on the menus, so they can share a key with these.
+    pub const IN_GAME: [PlayerAction; 6] = [
+        PlayerAction::Thrust,
+        PlayerAction::Brake,
+        PlayerAction::RotateLeft,
+        PlayerAction::RotateRight,
+        PlayerAction::Fire,
+        PlayerAction::Pause,
+    ];
+
+    /// Returns the name of the action in the key bindings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            PlayerAction::Thrust => "thrust",
+            PlayerAction::Brake => "brake",
+            PlayerAction::RotateLeft => "rotate_left",
+            PlayerAction::RotateRight => "rotate_right",
+            PlayerAction::Fire => "fire",
+            PlayerAction::Pause => "pause",
+            PlayerAction::MenuUp => "menu_up",
+            PlayerAction::MenuDown => "menu_down",
+            PlayerAction::MenuLeft => "menu_left",
+            PlayerAction::MenuRight => "menu_right",
+            PlayerAction::MenuConfirm => "menu_confirm",
+            PlayerAction::MenuBack => "menu_back",
+            PlayerAction::Start => "start",
+            PlayerAction::Restart => "restart",
+        }
+    }
+
+    /// Returns the name of the action shown to the player.
+    pub fn label(self) -> &'static str {
+        match self {
+            PlayerAction::Thrust => "Thrust",
+            PlayerAction::Brake => "Brake / Reverse",
+            PlayerAction::RotateLeft => "Rotate Left",
+            PlayerAction::RotateRight => "Rotate Right",
+            PlayerAction::Fire => "Fire",
+            PlayerAction::Pause => "Pause",
+            PlayerAction::MenuUp => "Menu Up",
+            PlayerAction::MenuDown => "Menu Down",
+            PlayerAction::MenuLeft => "Menu Left",
+            PlayerAction::MenuRight => "Menu Right",
+            PlayerAction::MenuConfirm => "Menu Confirm",
+            PlayerAction::MenuBack => "Menu Back",
+            PlayerAction::Start => "Quick Start",
+            PlayerAction::Restart => "Quick Restart",
+        }
+    }
+
+    /// Returns the keys bound to the action by default.
+    pub fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            PlayerAction::Thrust => vec![KeyCode::KeyW, KeyCode::ArrowUp],
+            PlayerAction::Brake => vec![KeyCode::KeyS, KeyCode::ArrowDown],
+            PlayerAction::RotateLeft => vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+            PlayerAction::RotateRight => vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            PlayerAction::Fire => vec![KeyCode::Space],
+            PlayerAction::Pause => vec![KeyCode::Escape, KeyCode::KeyP],
+            PlayerAction::MenuUp => vec![KeyCode::ArrowUp, KeyCode::KeyW],
+            PlayerAction::MenuDown => vec![KeyCode::ArrowDown, KeyCode::KeyS],
+            PlayerAction::MenuLeft => vec![KeyCode::ArrowLeft, KeyCode::KeyA],
+            PlayerAction::MenuRight => vec![KeyCode::ArrowRight, KeyCode::KeyD],
+            PlayerAction::MenuConfirm => vec![KeyCode::Enter, KeyCode::NumpadEnter],
+            PlayerAction::MenuBack => vec![KeyCode::Escape],
+            PlayerAction::Start => vec![KeyCode::Space],
+            PlayerAction::Restart => vec![KeyCode::KeyR],
+        }
+    }
+
+    /// Returns the gamepad buttons bound to the action.
+    ///
+    /// Thrust and brake have no buttons, as they follow how far the right and left
+    /// triggers are pulled, see [`AnalogInput`].
+    pub fn gamepad_buttons(self) -> &'static [GamepadButton] {
+        match self {
+            PlayerAction::Thrust | PlayerAction::Brake => &[],
+            PlayerAction::RotateLeft => &[GamepadButton::DPadLeft],
+            PlayerAction::RotateRight => &[GamepadButton::DPadRight],
+            PlayerAction::Fire => &[GamepadButton::South, GamepadButton::RightTrigger],
+            PlayerAction::Pause => &[GamepadButton::Start],
+            PlayerAction::MenuUp => &[GamepadButton::DPadUp],
+            PlayerAction::MenuDown => &[GamepadButton::DPadDown],
+            PlayerAction::MenuLeft => &[GamepadButton::DPadLeft],
+            PlayerAction::MenuRight => &[GamepadButton::DPadRight],
+            PlayerAction::MenuConfirm => &[GamepadButton::South],
+            PlayerAction::MenuBack => &[GamepadButton::East],
+            PlayerAction::Start => &[GamepadButton::Start],
+            PlayerAction::Restart => &[GamepadButton::Start],
+        }
+    }
+}
+
+/// Which actions are held, and which were pressed or released this frame.
+///
+/// This is resolved from the `InputMap` at the start of every frame, and is what the
+/// gameplay and the menus read instead of the keyboard.
+pub type ActionState = ButtonInput<PlayerAction>;
+
+/// How the player steers the ship.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ControlScheme {
+    /// The rotate actions turn the ship, and thrust pushes it the way it is facing.
+    #[default]
+    Tank,
+    /// The ship turns to face the mouse cursor. The right mouse button thrusts, and the
+    /// left mouse button fires.
+    MouseAim,
+}
+
+impl ControlScheme {
+    /// Returns the name of the scheme in the key bindings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            ControlScheme::Tank => "tank",
+            ControlScheme::MouseAim => "mouse_aim",
+        }
+    }
+
+    /// Returns the name of the scheme shown to the player.
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlScheme::Tank => "Tank",
+            ControlScheme::MouseAim => "Mouse Aim",
+        }
+    }
+
+    /// Returns the other scheme.
+    pub fn toggled(self) -> Self {
+        match self {
+            ControlScheme::Tank => ControlScheme::MouseAim,
+            ControlScheme::MouseAim => ControlScheme::Tank,
+        }
+    }
+
+    /// Returns the mouse buttons bound to an action with this scheme.
+    pub fn mouse_buttons(self, action: PlayerAction) -> &'static [MouseButton] {
+        match (self, action) {
+            (ControlScheme::MouseAim, PlayerAction::Thrust) => &[MouseButton::Right],
+            (ControlScheme::MouseAim, PlayerAction::Fire) => &[MouseButton::Left],
+            _ => &[],
+        }
+    }
+
+    /// Updates the scheme from the `key value` pairs of a settings file, if it is there.
+    pub fn apply_entries(&mut self, entries: &HashMap<String, String>) {
+        let scheme = [ControlScheme::Tank, ControlScheme::MouseAim]
+            .into_iter()
+            .find(|scheme| entries.get("control_scheme") == Some(&scheme.name().to_string()));
+
+        if let Some(scheme) = scheme {
+            *self = scheme;
+        }
+    }
+}
+
+/// Where the mouse cursor points in the world, or `None` while it is outside the window.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct CursorPosition(pub Option<Vec2>);
+
+/// How far the left stick has to be pushed sideways before the ship starts to turn.
+pub const STICK_DEADZONE: f32 = 0.2;
+
+/// How far a trigger has to be pulled before the ship starts to thrust.
+pub const TRIGGER_DEADZONE: f32 = 0.05;
+
+/// The analog input of the connected gamepads this frame.
+///
+/// Both are rescaled from the edge of their deadzone, so a stick pushed just past the
+/// deadzone turns the ship slowly instead of jumping to the deadzone's turn rate.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnalogInput {
+    /// How hard to thrust, from -1 for full reverse to 1 for full thrust, from the
+    /// triggers.
+    pub thrust: f32,
+    /// How fast to turn, from -1 for clockwise to 1 for counter-clockwise, from the left
+    /// stick.
+    pub turn: f32,
+}
+
+/// Rescales an analog value so that the deadzone reads as 0 and the rest of the range
+/// goes from 0 to 1, keeping its sign.
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    let magnitude = ((value.abs() - deadzone) / (1.0 - deadzone)).clamp(0.0, 1.0);
+    magnitude.copysign(value)
+}
+
+/// The radius of the on-screen joystick, in pixels.
+pub const TOUCH_STICK_RADIUS: f32 = 70.0;
+
+/// The radius of the on-screen fire button, in pixels.
+pub const TOUCH_FIRE_RADIUS: f32 = 50.0;
+
+/// How far the on-screen controls are from the edges of the screen, in pixels.
+pub const TOUCH_MARGIN: f32 = 40.0;
+
+/// How far outside of an on-screen control a touch can start and still grab it, as a
+/// fraction of its radius.
+pub const TOUCH_GRAB_RANGE: f32 = 1.5;
+
+/// The on-screen joystick and fire button, for playing on a touch screen.
+///
+/// The joystick sits in the bottom left corner of the screen and the fire button in the
+/// bottom right. A touch that starts on one of them holds it until the touch ends.
+/// Positions are in screen pixels, from the top left corner.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct TouchControls {
+    /// Whether the screen has been touched, which shows the on-screen controls.
+    pub active: bool,
+    /// How far the joystick is pushed, up to 1, with y pointing up.
+    pub stick: Vec2,
+    /// Whether the fire button is held, or was tapped this frame.
+    pub fire: bool,
+    /// The touch holding the joystick.
+    stick_touch: Option<u64>,
+    /// The touch holding the fire button.
+    fire_touch: Option<u64>,
+}
+
+impl TouchControls {
+    /// Returns the centre of the joystick on a screen of the given size.
+    pub fn stick_center(screen: Vec2) -> Vec2 {
+        let offset = TOUCH_MARGIN + TOUCH_STICK_RADIUS;
+        Vec2::new(offset, screen.y - offset)
+    }
+
+    /// Returns the centre of the fire button on a screen of the given size.
+    pub fn fire_center(screen: Vec2) -> Vec2 {
+        let offset = TOUCH_MARGIN + TOUCH_FIRE_RADIUS;
+        Vec2::new(screen.x - offset, screen.y - offset)
+    }
+
+    /// Updates the controls from the touches of this frame.
+    ///
+    /// # Arguments
+    /// * `touches`: The touches on the screen.
+    /// * `screen`: The size of the screen.
+    pub fn update(&mut self, touches: &Touches, screen: Vec2) {
+        let stick_center = Self::stick_center(screen);
+        let fire_center = Self::fire_center(screen);
+        let mut tapped_fire = false;
+
+        for touch in touches.iter_just_pressed() {
+            self.active = true;
+
+            let position = touch.position();
+            if self.stick_touch.is_none()
+                && position.distance(stick_center) <= TOUCH_STICK_RADIUS * TOUCH_GRAB_RANGE
+            {
+                self.stick_touch = Some(touch.id());
+            } else if self.fire_touch.is_none()
+                && position.distance(fire_center) <= TOUCH_FIRE_RADIUS * TOUCH_GRAB_RANGE
+            {
+                self.fire_touch = Some(touch.id());
+                // A tap can start and end within a single frame.
+                tapped_fire = true;
+            }
+        }
+
+        self.stick_touch = self
+            .stick_touch
+            .filter(|id| touches.get_pressed(*id).is_some());
+        self.fire_touch = self
+            .fire_touch
+            .filter(|id| touches.get_pressed(*id).is_some());
+
+        self.stick = match self.stick_touch.and_then(|id| touches.get_pressed(id)) {
+            Some(touch) => {
+                let offset = (touch.position() - stick_center) / TOUCH_STICK_RADIUS;
+                Vec2::new(offset.x, -offset.y).clamp_length_max(1.0)
+            }
+            None => Vec2::ZERO,
+        };
+        self.fire = tapped_fire || self.fire_touch.is_some();
+    }
+}
+
+/// The keys bound to every `PlayerAction`.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct InputMap {
+    bindings: HashMap<PlayerAction, Vec<KeyCode>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: PlayerAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl InputMap {
+    /// Returns the keys bound to an action.
+    pub fn keys(&self, action: PlayerAction) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds an action to a single key, replacing its other keys.
+    ///
+    /// A key bound to one of the `PlayerAction::IN_GAME` actions is taken off the others,
+    /// so that a single key never does two things while flying.
+    pub fn bind(&mut self, action: PlayerAction, key: KeyCode) {
+        if PlayerAction::IN_GAME.contains(&action) {
+            for other in PlayerAction::IN_GAME {
+                if let Some(keys) = self.bindings.get_mut(&other) {
+                    keys.retain(|bound| *bound != key);
+                }
+            }
+        }
+
+        self.bindings.insert(action, vec![key]);
+    }
+
+    /// Returns the keys bound to an action as text for the player, like `W / Up`.
+    pub fn keys_label(&self, action: PlayerAction) -> String {
+        let keys = self.keys(action);
+
+        if keys.is_empty() {
+            return "-".to_string();
+        }
+
+        keys.iter()
+            .map(|key| key_label(*key))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Returns the bindings as `key value` pairs for a settings file.
+    pub fn to_entries(&self) -> Vec<(&'static str, String)> {
+        PlayerAction::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = self
+                    .keys(action)
+                    .iter()
+                    .map(|key| format!("{key:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                (action.name(), keys)
+            })
+            .collect()
+    }
+
+    /// Updates the bindings from the `key value` pairs of a settings file.
+    ///
+    /// Actions that are missing, or have no valid keys, keep their bindings.
+    pub fn apply_entries(&mut self, entries: &HashMap<String, String>) {
+        for action in PlayerAction::ALL {
+            let Some(value) = entries.get(action.name()) else {
+                continue;
+            };
+
+            let keys = value
+                .split_whitespace()
+                .filter_map(parse_key)
+                .collect::<Vec<_>>();
+            if !keys.is_empty() {
+                self.bindings.insert(action, keys);
+            }
+        }
+    }
+}
+
+/// Where the `InputMap` is saved between sessions. `None` keeps it in memory only.
+#[derive(Resource, Debug, Clone)]
+pub struct InputMapFile(pub Option<PathBuf>);
+
+impl Default for InputMapFile {
+    fn default() -> Self {
+        Self(data_file("controls.txt"))
+    }
+}
+
+/// The action being rebound in the controls menu, waiting for the player to press a key.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Rebinding(pub PlayerAction);
+
+/// Every key an action can be bound to.
+pub const BINDABLE_KEYS: [KeyCode; 98] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Escape,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Backquote,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Backslash,
+    KeyCode::Semicolon,
+    KeyCode::Quote,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+    KeyCode::NumpadAdd,
+    KeyCode::NumpadSubtract,
+    KeyCode::NumpadMultiply,
+    KeyCode::NumpadDivide,
+    KeyCode::NumpadDecimal,
+    KeyCode::NumpadEnter,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::CapsLock,
+    KeyCode::Pause,
+];
+
+/// Returns the bindable key with the given name, like `KeyW`.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .into_iter()
+        .find(|key| format!("{key:?}") == name)
+}
+
+/// Returns the name of a key shown to the player, like `W` for `KeyW`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+
+    ["Key", "Digit", "Arrow"]
+        .into_iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(&name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        input::touch::{TouchInput, TouchPhase, touch_screen_input_system},
+    };
+
+    use super::*;
+
+    const SCREEN: Vec2 = Vec2::new(800.0, 600.0);
+
+    #[test]
+    fn binding_a_key_takes_it_off_the_other_in_game_actions() {
+        let mut input_map = InputMap::default();
+
+        input_map.bind(PlayerAction::Fire, KeyCode::KeyW);
+        assert_eq!(input_map.keys(PlayerAction::Fire), [KeyCode::KeyW]);
+        assert_eq!(input_map.keys(PlayerAction::Thrust), [KeyCode::ArrowUp]);
+
+        // The last key of an action can be taken too, which leaves it unbound.
+        input_map.bind(PlayerAction::Brake, KeyCode::KeyW);
+        assert_eq!(input_map.keys(PlayerAction::Fire), []);
+        assert_eq!(input_map.keys_label(PlayerAction::Fire), "-");
+    }
+
+    #[test]
+    fn menu_actions_keep_keys_shared_with_in_game_actions() {
+        let mut input_map = InputMap::default();
+
+        // Fire and quick start share `Space` by default, on different screens.
+        input_map.bind(PlayerAction::Fire, KeyCode::Space);
+        assert_eq!(input_map.keys(PlayerAction::Start), [KeyCode::Space]);
+
+        input_map.bind(PlayerAction::Restart, KeyCode::KeyW);
+        assert_eq!(
+            input_map.keys(PlayerAction::Thrust),
+            [KeyCode::KeyW, KeyCode::ArrowUp]
+        );
+        assert_eq!(
+            input_map.keys(PlayerAction::MenuUp),
+            [KeyCode::ArrowUp, KeyCode::KeyW]
+        );
+    }
+
+    /// Plays a frame with the given touch events and updates the controls from it.
+    fn frame(world: &mut World, controls: &mut TouchControls, events: &[(TouchPhase, u64, Vec2)]) {
+        for &(phase, id, position) in events {
+            world.send_event(TouchInput {
+                phase,
+                position,
+                window: Entity::PLACEHOLDER,
+                force: None,
+                id,
+            });
+        }
+
+        world.run_system_once(touch_screen_input_system).unwrap();
+        world.resource_mut::<Events<TouchInput>>().clear();
+
+        controls.update(world.resource::<Touches>(), SCREEN);
+    }
+
+    fn touch_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Touches>();
+        world.init_resource::<Events<TouchInput>>();
+        world
+    }
+
+    #[test]
+    fn dragging_the_stick_steers() {
+        let mut world = touch_world();
+        let mut controls = TouchControls::default();
+        let center = TouchControls::stick_center(SCREEN);
+
+        frame(
+            &mut world,
+            &mut controls,
+            &[(TouchPhase::Started, 1, center)],
+        );
+        assert!(controls.active);
+        assert_eq!(controls.stick, Vec2::ZERO);
+
+        let right = center + Vec2::X * TOUCH_STICK_RADIUS / 2.0;
+        frame(&mut world, &mut controls, &[(TouchPhase::Moved, 1, right)]);
+        assert_eq!(controls.stick, Vec2::new(0.5, 0.0));
+
+        // Screen y points down, and past the edge the stick stays fully pushed.
+        let far_up = center - Vec2::Y * TOUCH_STICK_RADIUS * 3.0;
+        frame(&mut world, &mut controls, &[(TouchPhase::Moved, 1, far_up)]);
+        assert_eq!(controls.stick, Vec2::Y);
+        assert!(!controls.fire);
+
+        frame(&mut world, &mut controls, &[(TouchPhase::Ended, 1, far_up)]);
+        assert_eq!(controls.stick, Vec2::ZERO);
+    }
+
+    #[test]
+    fn tapping_fire_fires_once() {
+        let mut world = touch_world();
+        let mut controls = TouchControls::default();
+        let center = TouchControls::fire_center(SCREEN);
+
+        // The touch starts and ends within the same frame.
+        frame(
+            &mut world,
+            &mut controls,
+            &[
+                (TouchPhase::Started, 2, center),
+                (TouchPhase::Ended, 2, center),
+            ],
+        );
+        assert!(controls.fire);
+        assert_eq!(controls.stick, Vec2::ZERO);
+
+        frame(&mut world, &mut controls, &[]);
+        assert!(!controls.fire);
+    }
+
+    #[test]
+    fn fire_is_held_until_the_touch_is_released() {
+        let mut world = touch_world();
+        let mut controls = TouchControls::default();
+        let center = TouchControls::fire_center(SCREEN);
+
+        frame(
+            &mut world,
+            &mut controls,
+            &[(TouchPhase::Started, 3, center)],
+        );
+        frame(&mut world, &mut controls, &[]);
+        assert!(controls.fire);
+
+        // Sliding off the button keeps holding it.
+        frame(
+            &mut world,
+            &mut controls,
+            &[(TouchPhase::Moved, 3, SCREEN / 2.0)],
+        );
+        assert!(controls.fire);
+
+        frame(
+            &mut world,
+            &mut controls,
+            &[(TouchPhase::Ended, 3, SCREEN / 2.0)],
+        );
+        assert!(!controls.fire);
+    }
+
+    #[test]
+    fn touches_away_from_the_controls_do_nothing() {
+        let mut world = touch_world();
+        let mut controls = TouchControls::default();
+
+        frame(
+            &mut world,
+            &mut controls,
+            &[(TouchPhase::Started, 4, SCREEN / 2.0)],
+        );
+        assert!(controls.active);
+        assert_eq!(controls.stick, Vec2::ZERO);
+        assert!(!controls.fire);
+    }
+}
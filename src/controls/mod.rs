@@ -0,0 +1,40 @@
+//! # Controls
+//!
+//! This module maps the player's input, from the keyboard, the mouse, any connected
+//! gamepad or the touch screen, to the actions the gameplay and the menus read.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
+
+use bevy::{input::InputSystem, prelude::*};
+
+/// Resolves the player's input into the `ActionState` at the start of every frame.
+///
+/// The key bindings are loaded from the `InputMapFile` by [`crate::AsteroidsPlugin`], so
+/// that simulations always play with the default keys.
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>();
+        app.init_resource::<InputMapFile>();
+        app.init_resource::<ActionState>();
+        app.init_resource::<AnalogInput>();
+        app.init_resource::<ControlScheme>();
+        app.init_resource::<CursorPosition>();
+        app.init_resource::<TouchControls>();
+
+        app.add_systems(
+            PreUpdate,
+            (
+                resolve_touch_controls,
+                (resolve_actions, resolve_analog_input).after(resolve_touch_controls),
+                resolve_cursor_position,
+                log_gamepad_connections,
+            )
+                .after(InputSystem),
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Systems for resolving the player's input into actions, and storing the key bindings.
+
+use std::io;
+
+use bevy::{
+    input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent},
+        touch::Touches,
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use super::{
+    ActionState, AnalogInput, ControlScheme, CursorPosition, InputMap, InputMapFile, PlayerAction,
+    STICK_DEADZONE, TRIGGER_DEADZONE, TouchControls, apply_deadzone,
+};
+use crate::{
+    arena::Arena,
+    storage::{load_settings, save_settings},
+};
+
+/// Resolves the touches on the screen into the `TouchControls` for this frame.
+///
+/// # Arguments
+/// * `touches`: The `Touches` resource with the touches on the screen.
+/// * `arena`: The `Arena` resource, which matches the size of the screen.
+/// * `controls`: The `TouchControls` resource to update.
+pub fn resolve_touch_controls(
+    touches: Res<Touches>,
+    arena: Res<Arena>,
+    mut controls: ResMut<TouchControls>,
+) {
+    let mut updated = controls.clone();
+    updated.update(&touches, arena.size);
+
+    controls.set_if_neq(updated);
+}
+
+/// Resolves the keyboard, the mouse, the gamepads and the on-screen fire button into the
+/// `ActionState` for this frame.
+///
+/// An action is held while any of its keys, its mouse buttons with the `ControlScheme`, or
+/// its buttons on any gamepad, are held. Fire is also held with the `TouchControls`.
+///
+/// # Arguments
+/// * `keyboard_input`: The `ButtonInput<KeyCode>` resource with the keys held this frame.
+/// * `mouse_input`: The `ButtonInput<MouseButton>` resource with the mouse buttons held.
+/// * `gamepads`: A query that retrieves every connected `Gamepad`.
+/// * `input_map`: The `InputMap` resource with the keys bound to every action.
+/// * `scheme`: The `ControlScheme` resource with the mouse buttons bound to every action.
+/// * `touch`: The `TouchControls` resource with the on-screen fire button.
+/// * `actions`: The `ActionState` resource to update.
+pub fn resolve_actions(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    input_map: Res<InputMap>,
+    scheme: Res<ControlScheme>,
+    touch: Res<TouchControls>,
+    mut actions: ResMut<ActionState>,
+) {
+    actions.clear();
+
+    for action in PlayerAction::ALL {
+        let buttons = action.gamepad_buttons();
+        let held = keyboard_input.any_pressed(input_map.keys(action).iter().copied())
+            || mouse_input.any_pressed(scheme.mouse_buttons(action).iter().copied())
+            || gamepads
+                .iter()
+                .any(|gamepad| gamepad.any_pressed(buttons.iter().copied()))
+            || (action == PlayerAction::Fire && touch.fire);
+
+        if held && !actions.pressed(action) {
+            actions.press(action);
+        } else if !held && actions.pressed(action) {
+            actions.release(action);
+        }
+    }
+}
+
+/// Resolves the sticks and triggers of the gamepads, and the on-screen joystick, into the
+/// `AnalogInput` for this frame.
+///
+/// The left stick turns the ship, the right trigger thrusts and the left trigger
+/// reverses. The on-screen joystick turns the ship sideways, thrusts pushed up and
+/// reverses pushed down. With several gamepads, or a gamepad and the touch screen, the
+/// one pushed furthest wins.
+///
+/// # Arguments
+/// * `gamepads`: A query that retrieves every connected `Gamepad`.
+/// * `touch`: The `TouchControls` resource with the on-screen joystick.
+/// * `analog`: The `AnalogInput` resource to update.
+pub fn resolve_analog_input(
+    gamepads: Query<&Gamepad>,
+    touch: Res<TouchControls>,
+    mut analog: ResMut<AnalogInput>,
+) {
+    let mut input = AnalogInput::default();
+
+    let sticks = gamepads.iter().map(|gamepad| {
+        let trigger = |button| {
+            let value = gamepad.get(button).unwrap_or(0.0);
+            apply_deadzone(value, TRIGGER_DEADZONE)
+        };
+        let thrust = trigger(GamepadButton::RightTrigger2) - trigger(GamepadButton::LeftTrigger2);
+
+        (gamepad.left_stick().x, thrust)
+    });
+    let touch_stick = (touch.stick.x, apply_deadzone(touch.stick.y, STICK_DEADZONE));
+
+    for (x, thrust) in sticks.chain([touch_stick]) {
+        // Pushing the stick right turns the ship clockwise.
+        let turn = -apply_deadzone(x, STICK_DEADZONE);
+        if turn.abs() > input.turn.abs() {
+            input.turn = turn;
+        }
+
+        if thrust.abs() > input.thrust.abs() {
+            input.thrust = thrust;
+        }
+    }
+
+    analog.set_if_neq(input);
+}
+
+/// Finds where the mouse cursor points in the world, through the 2D camera.
+///
+/// # Arguments
+/// * `windows`: A query that retrieves the primary window with the cursor.
+/// * `cameras`: A query that retrieves the 2D camera and where it is.
+/// * `cursor`: The `CursorPosition` resource to update.
+pub fn resolve_cursor_position(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    mut cursor: ResMut<CursorPosition>,
+) {
+    let position = windows
+        .single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .zip(cameras.single().ok())
+        .and_then(|(position, (camera, transform))| {
+            camera.viewport_to_world_2d(transform, position).ok()
+        });
+
+    cursor.set_if_neq(CursorPosition(position));
+}
+
+/// Logs gamepads being plugged in and unplugged.
+///
+/// Every connected gamepad is read, so a gamepad can be plugged in at any time.
+///
+/// # Arguments
+/// * `events`: The `EventReader` for the `GamepadConnectionEvent`s.
+pub fn log_gamepad_connections(mut events: EventReader<GamepadConnectionEvent>) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected { name, .. } => {
+                log::info!("Gamepad connected: {name}");
+            }
+            GamepadConnection::Disconnected => {
+                log::info!("Gamepad disconnected: {}", event.gamepad);
+            }
+        }
+    }
+}
+
+/// Loads the `InputMap` and the `ControlScheme` from their file when the game starts.
+///
+/// # Arguments
+/// * `input_map`: The `InputMap` resource to load into.
+/// * `scheme`: The `ControlScheme` resource to load into.
+/// * `file`: The `InputMapFile` resource with the path to load from.
+pub fn load_input_map(
+    mut input_map: ResMut<InputMap>,
+    mut scheme: ResMut<ControlScheme>,
+    file: Res<InputMapFile>,
+) {
+    let Some(path) = &file.0 else {
+        return;
+    };
+
+    match load_settings(path) {
+        Ok(entries) => {
+            input_map.apply_entries(&entries);
+            scheme.apply_entries(&entries);
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::error!(
+            "Failed to load key bindings from {}: {error}",
+            path.display()
+        ),
+    }
+}
+
+/// Saves the `InputMap` and the `ControlScheme` to their file, if they have one.
+///
+/// # Arguments
+/// * `input_map`: The `InputMap` resource to save.
+/// * `scheme`: The `ControlScheme` resource to save.
+/// * `file`: The `InputMapFile` resource with the path to save to.
+pub fn save_input_map(
+    input_map: Res<InputMap>,
+    scheme: Res<ControlScheme>,
+    file: Res<InputMapFile>,
+) {
+    let Some(path) = &file.0 else {
+        return;
+    };
+
+    let mut entries = input_map.to_entries();
+    entries.push(("control_scheme", scheme.name().to_string()));
+
+    if let Err(error) = save_settings(path, &entries) {
+        log::error!("Failed to save key bindings to {}: {error}", path.display());
+    }
+}
@@ -0,0 +1,424 @@
+//! Data for the high score table, and its file format.
+//!
+//! The high score file is plain text. The header holds the format version, and is
+//! followed by one line per entry from the best score down, with the score, how long
+//! the run lasted in milliseconds, the run's seed, when the run ended in seconds since
+//! the Unix epoch (or `-` if that isn't known), and the player's name:
+//!
+//! ```text
+//! asteroids-highscores 1
+//! 120 95500 1234 1760000000 ACE
+//! 45 40250 99 - BOB
+//! ```
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::storage::data_file;
+
+/// The first word of every high score file.
+pub const HIGH_SCORES_MAGIC: &str = "asteroids-highscores";
+
+/// The version of the high score file format that is written.
+pub const HIGH_SCORES_VERSION: u32 = 1;
+
+/// How many runs the high score table keeps.
+pub const HIGH_SCORE_COUNT: usize = 10;
+
+/// The longest name the player can enter for a high score.
+pub const MAX_NAME_LENGTH: usize = 10;
+
+/// The name used for a high score when the player doesn't enter one.
+pub const DEFAULT_NAME: &str = "???";
+
+/// A run in the high score table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    /// The name the player entered.
+    pub name: String,
+    /// The final score of the run.
+    pub score: i32,
+    /// How long the run lasted.
+    pub duration: Duration,
+    /// The seed the run's `GameRng` started from.
+    pub seed: u64,
+    /// When the run ended, in seconds since the Unix epoch. `None` if the clock isn't available.
+    pub recorded_at: Option<u64>,
+}
+
+impl HighScore {
+    /// Creates an entry without a name for a run that just ended.
+    pub fn new(score: i32, duration: Duration, seed: u64) -> Self {
+        // There is no system clock through `std` on the web.
+        let recorded_at = if cfg!(target_arch = "wasm32") {
+            None
+        } else {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since_epoch| since_epoch.as_secs())
+        };
+
+        Self {
+            name: String::new(),
+            score,
+            duration,
+            seed,
+            recorded_at,
+        }
+    }
+
+    /// Returns the day the run ended on as `YYYY-MM-DD` in UTC, if it is known.
+    pub fn date(&self) -> Option<String> {
+        // Converts days since the Unix epoch to a civil date, from
+        // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+        let days = (self.recorded_at? / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Some(format!("{year:04}-{month:02}-{day:02}"))
+    }
+}
+
+/// The best runs so far, from the best score down.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+    /// The rank of the entry added this session, to highlight it. Not saved.
+    pub latest: Option<usize>,
+}
+
+impl HighScores {
+    /// Returns the entries from the best score down.
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// Returns `true` if a run with the given score would make it into the table.
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0
+            && (self.entries.len() < HIGH_SCORE_COUNT
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds an entry to the table, dropping the lowest one if the table is full.
+    ///
+    /// Returns the rank of the new entry starting at 0, or `None` if it didn't make it in.
+    /// An entry that ties an older one is ranked below it.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+
+        if rank >= HIGH_SCORE_COUNT {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORE_COUNT);
+        self.latest = Some(rank);
+        Some(rank)
+    }
+
+    /// Writes the table in the high score file format.
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "{HIGH_SCORES_MAGIC} {HIGH_SCORES_VERSION}")?;
+
+        for entry in &self.entries {
+            let recorded_at = match entry.recorded_at {
+                Some(recorded_at) => recorded_at.to_string(),
+                None => "-".to_string(),
+            };
+
+            writeln!(
+                writer,
+                "{} {} {} {} {}",
+                entry.score,
+                entry.duration.as_millis(),
+                entry.seed,
+                recorded_at,
+                entry.name
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a table written by [`HighScores::write`].
+    pub fn read(reader: impl BufRead) -> Result<Self, HighScoreError> {
+        let mut lines = reader.lines().enumerate();
+
+        match lines.next() {
+            Some((_, magic)) => match magic?.split_once(' ') {
+                Some((HIGH_SCORES_MAGIC, version))
+                    if version == HIGH_SCORES_VERSION.to_string() => {}
+                Some((HIGH_SCORES_MAGIC, version)) => {
+                    return Err(HighScoreError::parse(
+                        1,
+                        format!("unsupported version {version}"),
+                    ));
+                }
+                _ => return Err(HighScoreError::parse(1, "not a high score file")),
+            },
+            None => return Err(HighScoreError::parse(0, "unexpected end of file")),
+        }
+
+        let mut scores = Self::default();
+
+        for (index, text) in lines {
+            let line = index + 1;
+            let text = text?;
+
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            let fields = text.splitn(5, ' ').collect::<Vec<_>>();
+            let [score, duration, seed, recorded_at, name] = fields[..] else {
+                return Err(HighScoreError::parse(line, "expected 5 fields"));
+            };
+
+            let recorded_at = match recorded_at {
+                "-" => None,
+                recorded_at => Some(parse_number(line, recorded_at)?),
+            };
+
+            scores.insert(HighScore {
+                name: name.to_string(),
+                score: parse_number(line, score)?,
+                duration: Duration::from_millis(parse_number(line, duration)?),
+                seed: parse_number(line, seed)?,
+                recorded_at,
+            });
+        }
+
+        scores.latest = None;
+        Ok(scores)
+    }
+
+    /// Saves the table to a file, creating its folder if needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a table from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, HighScoreError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Parses a number on the given line.
+fn parse_number<T: std::str::FromStr>(line: usize, text: &str) -> Result<T, HighScoreError> {
+    text.trim()
+        .parse()
+        .map_err(|_| HighScoreError::parse(line, format!("invalid number `{text}`")))
+}
+
+/// An error from reading a high score file.
+#[derive(Debug)]
+pub enum HighScoreError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file isn't a valid high score file.
+    Parse {
+        /// The line the error is on, starting at 1.
+        line: usize,
+        /// What is wrong with the line.
+        message: String,
+    },
+}
+
+impl HighScoreError {
+    fn parse(line: usize, message: impl Into<String>) -> Self {
+        Self::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HighScoreError::Io(error) => write!(f, "failed to read high scores: {error}"),
+            HighScoreError::Parse { line, message } => {
+                write!(f, "invalid high scores on line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HighScoreError {}
+
+impl From<io::Error> for HighScoreError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Settings for storing the high score table.
+#[derive(Resource, Debug, Clone)]
+pub struct HighScoreSettings {
+    /// The file the table is loaded from and saved to. `None` keeps it in memory only.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for HighScoreSettings {
+    fn default() -> Self {
+        Self {
+            path: data_file("highscores.txt"),
+        }
+    }
+}
+
+/// A run that made it into the high score table, waiting for the player to enter a name.
+#[derive(Resource, Debug, Clone)]
+pub struct PendingHighScore(pub HighScore);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: i32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            duration: Duration::from_millis(40_250),
+            seed: 99,
+            recorded_at: None,
+        }
+    }
+
+    fn names(scores: &HighScores) -> Vec<&str> {
+        scores
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_through_the_file_format() {
+        let mut scores = HighScores::default();
+        scores.insert(HighScore {
+            recorded_at: Some(1_760_000_000),
+            ..entry("ACE", 120)
+        });
+        // Names can have spaces in them, as they are the last field.
+        scores.insert(entry("BIG BOB", 45));
+
+        let mut file = Vec::new();
+        scores.write(&mut file).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&file).unwrap(),
+            "asteroids-highscores 1\n120 40250 99 1760000000 ACE\n45 40250 99 - BIG BOB\n"
+        );
+
+        let read = HighScores::read(file.as_slice()).unwrap();
+        assert_eq!(read.entries(), scores.entries());
+        assert_eq!(read.latest, None);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let line_of = |text: &str| match HighScores::read(text.as_bytes()) {
+            Err(HighScoreError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {other:?}"),
+        };
+
+        assert_eq!(line_of(""), 0);
+        assert_eq!(line_of("asteroids-replay 1\n"), 1);
+        assert_eq!(line_of("asteroids-highscores 2\n"), 1);
+        assert_eq!(line_of("asteroids-highscores 1\n120 95500 1234\n"), 2);
+        assert_eq!(
+            line_of("asteroids-highscores 1\n120 95500 1234 - ACE\nlots 1 2 - BOB\n"),
+            3
+        );
+    }
+
+    #[test]
+    fn keeps_the_best_entries_of_a_full_table() {
+        let mut scores = HighScores::default();
+        for score in 1..=HIGH_SCORE_COUNT as i32 {
+            scores.insert(entry(&score.to_string(), score * 10));
+        }
+
+        assert!(!scores.qualifies(10));
+        assert!(scores.qualifies(11));
+
+        assert_eq!(scores.insert(entry("LOW", 5)), None);
+        assert_eq!(scores.insert(entry("NEW", 55)), Some(5));
+        assert_eq!(scores.latest, Some(5));
+        assert_eq!(scores.entries().len(), HIGH_SCORE_COUNT);
+
+        // The lowest entry was dropped to make room.
+        assert_eq!(scores.entries().last().unwrap().score, 20);
+    }
+
+    #[test]
+    fn ranks_ties_below_older_entries() {
+        let mut scores = HighScores::default();
+        scores.insert(entry("OLD", 50));
+        scores.insert(entry("LOW", 10));
+
+        assert_eq!(scores.insert(entry("NEW", 50)), Some(1));
+        assert_eq!(names(&scores), ["OLD", "NEW", "LOW"]);
+    }
+
+    #[test]
+    fn only_positive_scores_qualify() {
+        let scores = HighScores::default();
+
+        assert!(!scores.qualifies(0));
+        assert!(!scores.qualifies(-5));
+        assert!(scores.qualifies(1));
+    }
+
+    #[test]
+    fn converts_timestamps_to_dates() {
+        let date = |recorded_at| {
+            HighScore {
+                recorded_at,
+                ..entry("ACE", 1)
+            }
+            .date()
+        };
+
+        assert_eq!(date(None), None);
+        assert_eq!(date(Some(0)).unwrap(), "1970-01-01");
+        assert_eq!(date(Some(951_782_400)).unwrap(), "2000-02-29");
+        assert_eq!(date(Some(1_709_251_199)).unwrap(), "2024-02-29");
+        assert_eq!(date(Some(1_709_251_200)).unwrap(), "2024-03-01");
+        assert_eq!(date(Some(1_760_000_000)).unwrap(), "2025-10-09");
+        // 2100 isn't a leap year.
+        assert_eq!(date(Some(4_107_542_400)).unwrap(), "2100-03-01");
+    }
+}
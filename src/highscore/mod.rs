@@ -0,0 +1,31 @@
+//! # High Scores
+//!
+//! This module keeps a table of the best live runs, saved in the user's data directory.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
+
+use crate::GameState;
+use bevy::prelude::*;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighScoreSettings>();
+        app.init_resource::<HighScores>();
+        app.add_systems(Startup, load_high_scores);
+
+        // Only live runs can make the table, replays end in the same state.
+        app.add_systems(
+            OnTransition {
+                exited: GameState::Game,
+                entered: GameState::GameOver,
+            },
+            check_high_score,
+        );
+        app.add_systems(OnExit(GameState::GameOver), discard_pending_high_score);
+    }
+}
@@ -0,0 +1,81 @@
+//! Systems for the high score table.
+
+use std::io;
+
+use bevy::prelude::*;
+
+use super::{HighScore, HighScoreError, HighScoreSettings, HighScores, PendingHighScore};
+use crate::{RunClock, rng::GameRng, ui::PlayerScore};
+
+/// Loads the high score table from its file when the game starts.
+///
+/// A missing file is an empty table, and a broken one is logged and replaced on the next save.
+///
+/// # Arguments
+/// * `scores`: The `HighScores` resource to load the table into.
+/// * `settings`: The `HighScoreSettings` resource to get the path to load from.
+pub fn load_high_scores(mut scores: ResMut<HighScores>, settings: Res<HighScoreSettings>) {
+    let Some(path) = &settings.path else {
+        return;
+    };
+
+    match HighScores::load(path) {
+        Ok(loaded) => {
+            log::info!("Loaded high scores from {}", path.display());
+            *scores = loaded;
+        }
+        Err(HighScoreError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => log::error!(
+            "Failed to load high scores from {}: {error}",
+            path.display()
+        ),
+    }
+}
+
+/// Saves the high score table to its file, if it has one.
+///
+/// # Arguments
+/// * `scores`: The high score table to save.
+/// * `settings`: The settings with the path to save to.
+pub fn save_high_scores(scores: &HighScores, settings: &HighScoreSettings) {
+    let Some(path) = &settings.path else {
+        return;
+    };
+
+    match scores.save(path) {
+        Ok(()) => log::info!("Saved high scores to {}", path.display()),
+        Err(error) => log::error!("Failed to save high scores to {}: {error}", path.display()),
+    }
+}
+
+/// Asks the player for a name when a live run ends with a score that makes the table.
+///
+/// # Arguments
+/// * `commands`: The `Commands` to insert the `PendingHighScore` resource.
+/// * `scores`: The `HighScores` resource to check the score against.
+/// * `score`: The `PlayerScore` resource with the final score.
+/// * `run_clock`: The `RunClock` resource that timed the run.
+/// * `rng`: The `GameRng` resource to get the run's seed from.
+pub fn check_high_score(
+    mut commands: Commands,
+    scores: Res<HighScores>,
+    score: Res<PlayerScore>,
+    run_clock: Res<RunClock>,
+    rng: Res<GameRng>,
+) {
+    if scores.qualifies(score.0) {
+        commands.insert_resource(PendingHighScore(HighScore::new(
+            score.0,
+            run_clock.0.elapsed(),
+            rng.seed(),
+        )));
+    }
+}
+
+/// Drops a high score the player never entered a name for.
+///
+/// # Arguments
+/// * `commands`: The `Commands` to remove the `PendingHighScore` resource.
+pub fn discard_pending_high_score(mut commands: Commands) {
+    commands.remove_resource::<PendingHighScore>();
+}
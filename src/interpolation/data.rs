@@ -0,0 +1,16 @@
+//! Data for interpolating transforms between fixed ticks.
+
+use bevy::prelude::*;
+
+/// Smooths an entity's `Transform` between fixed ticks.
+///
+/// The gameplay moves entities in `FixedUpdate`, which doesn't line up with rendered
+/// frames. During fixed ticks the `Transform` holds the simulated state, and in between
+/// it is blended from the last two ticks so movement looks smooth at any frame rate.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct TransformInterpolation {
+    /// The simulated transform at the end of the tick before last.
+    pub previous: Option<Transform>,
+    /// The simulated transform at the end of the last tick.
+    pub current: Option<Transform>,
+}
@@ -0,0 +1,23 @@
+//! # Interpolation
+//!
+//! This module smooths the movement of entities simulated in `FixedUpdate` for rendering.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
+
+use bevy::prelude::*;
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, restore_simulated_transforms);
+        app.add_systems(FixedLast, store_simulated_transforms);
+        app.add_systems(
+            RunFixedMainLoop,
+            interpolate_transforms.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Systems for interpolating transforms between fixed ticks.
+
+use bevy::prelude::*;
+
+use super::TransformInterpolation;
+
+/// Puts back the simulated transforms before a fixed tick, undoing the interpolation.
+///
+/// # Arguments
+/// * `query`: A query that retrieves every interpolated `Transform`.
+pub fn restore_simulated_transforms(
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        if let Some(current) = interpolation.current {
+            *transform = current;
+        }
+
+        interpolation.previous = Some(*transform);
+    }
+}
+
+/// Stores the simulated transforms after a fixed tick.
+///
+/// # Arguments
+/// * `query`: A query that retrieves every interpolated `Transform`.
+pub fn store_simulated_transforms(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in query.iter_mut() {
+        interpolation.current = Some(*transform);
+
+        // Entities spawned during the tick have nowhere to come from.
+        if interpolation.previous.is_none() {
+            interpolation.previous = Some(*transform);
+        }
+    }
+}
+
+/// Blends the transforms between the last two fixed ticks for rendering.
+///
+/// # Arguments
+/// * `query`: A query that retrieves every interpolated `Transform`.
+/// * `fixed_time`: The `Time<Fixed>` resource to get how far into the next tick we are.
+pub fn interpolate_transforms(
+    mut query: Query<(&mut Transform, &TransformInterpolation)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let t = fixed_time.overstep_fraction();
+
+    for (mut transform, interpolation) in query.iter_mut() {
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current)
+        else {
+            continue;
+        };
+
+        *transform = Transform {
+            translation: previous.translation.lerp(current.translation, t),
+            rotation: previous.rotation.slerp(current.rotation, t),
+            scale: previous.scale.lerp(current.scale, t),
+        };
+    }
+}
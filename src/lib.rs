@@ -5,26 +5,65 @@
 // Many bevy systems require >7 arguments, which is not allowed by clippy.
 #![allow(clippy::too_many_arguments)]
 
+pub mod arena;
 pub mod asteroid;
 pub mod audio;
 pub mod bullet;
+pub mod collision;
+pub mod controls;
 pub mod explosion;
+pub mod highscore;
+pub mod interpolation;
+pub mod pause;
+pub mod physics;
+pub mod replay;
+pub mod rng;
 pub mod ship;
+pub mod stats;
+pub mod storage;
 pub mod ui;
 
-use asteroid::{check_asteroid_bounds, move_asteroids, spawn_asteroids};
-use audio::main_song::play_main_song;
+use std::time::Duration;
+
+use arena::{Arena, spawn_wrap_ghosts, sync_arena_to_window, update_wrap_ghosts};
+use asteroid::{AsteroidSpawnTimer, check_asteroid_bounds, move_asteroids, spawn_asteroids};
+use audio::{
+    GameAudioPlugin, finish_loading, load_audio_assets,
+    main_song::{DuckMusic, play_main_song},
+    validate_audio_assets,
+};
 use bevy::{
-    app::PanicHandlerPlugin, diagnostic::DiagnosticsPlugin, prelude::*,
-    render::mesh::VertexAttributeValues,
+    app::PanicHandlerPlugin,
+    audio::{AudioLoader, Pitch},
+    diagnostic::DiagnosticsPlugin,
+    input::InputPlugin,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::{Stopwatch, TimeUpdateStrategy},
+};
+use bullet::{BulletPlugin, resolve_bullet_hits};
+use collision::{CollisionPlugin, rebuild_asteroid_grid};
+use controls::{
+    ControlsPlugin, load_input_map, resolve_actions, resolve_analog_input, resolve_cursor_position,
 };
-use bullet::BulletPlugin;
 use explosion::{setup_explosions, systems::explosion_system};
+use highscore::HighScorePlugin;
+use interpolation::InterpolationPlugin;
+use pause::{
+    PausePlugin, PauseState, pause_on_focus_loss, pause_sound_effects, resume_sound_effects,
+};
+use replay::{Replay, ReplayPlugin, start_replay};
+use rng::{GameRng, RngSeed, reseed_game_rng};
 use ship::*;
+use stats::{RunStats, finish_stats, restart_stats};
 
-use crate::{asteroid::despawn_asteroids, bullet::despawn_bullets, ui::GameUiPlugin};
+use crate::{
+    asteroid::despawn_asteroids,
+    bullet::despawn_bullets,
+    ui::{GameUiPlugin, PlayerScore, ScoreEvent, restart_score, tally_score},
+};
 
-/// The main plugin for the game, which sets up the game state and systems.
+/// The main plugin for the game, which opens a window and runs the game in it.
 pub struct AsteroidsPlugin;
 
 impl Plugin for AsteroidsPlugin {
@@ -46,92 +85,395 @@ impl Plugin for AsteroidsPlugin {
 
         app.add_plugins(default_plugins);
 
-        // Add GameState
-        app.init_state::<GameState>();
-
         // Make the background black.
         app.insert_resource(ClearColor(Color::BLACK));
 
-        // Set the fixed time step - this is how often we
-        // check to see if we spawn asteroids
-        app.insert_resource(Time::<Fixed>::from_seconds(0.5));
+        app.add_plugins(AsteroidsGameplayPlugin::default());
+
+        // Keep the arena the same size as the window.
+        app.add_systems(PreUpdate, sync_arena_to_window);
 
-        // Setup the bullet and explosion resources.
-        app.add_systems(PostStartup, setup_explosions);
+        // Smooth out the fixed ticks for rendering.
+        app.add_plugins(InterpolationPlugin);
+
+        // Draw objects on both sides of the edges of a wrapping arena.
+        app.add_systems(Update, (spawn_wrap_ghosts, update_wrap_ghosts).chain());
 
         // Startup -> Loading -> Main Menu
+        app.add_systems(Update, finish_loading.run_if(in_state(GameState::Loading)));
         app.add_plugins(GameUiPlugin);
 
+        // Keep the best live runs between sessions.
+        app.add_plugins(HighScorePlugin);
+
+        // Play with the player's own key bindings.
+        app.add_systems(Startup, load_input_map);
+
+        // Pause when the player looks away, and keep the sound down while paused.
+        app.add_systems(Update, pause_on_focus_loss.run_if(in_state(InGame)));
+        app.add_systems(OnEnter(PauseState::Paused), pause_sound_effects);
+        app.add_systems(OnExit(PauseState::Paused), resume_sound_effects);
+
+        // Play every sound at the volumes the player picked.
+        app.add_plugins(GameAudioPlugin);
+
+        // Add the camera, and start the main song once it has loaded.
+        app.add_systems(PostStartup, spawn_camera);
+        app.add_systems(OnExit(GameState::Loading), play_main_song);
+    }
+}
+
+/// The gameplay of the game, without any window, UI or music.
+///
+/// This plugin expects the app to already provide states, assets for `Mesh`,
+/// `ColorMaterial`, `AudioSource` and `Pitch`, and a `Time` source. Use [`AsteroidsPlugin`]
+/// to play the game, or [`AsteroidsHeadlessPlugin`] to simulate it.
+#[derive(Default)]
+pub struct AsteroidsGameplayPlugin {
+    /// The initial size of the arena the game is played in.
+    pub arena: Arena,
+    /// The seed every run starts from. `None` picks a new seed for every run.
+    pub seed: Option<u64>,
+}
+
+impl Plugin for AsteroidsGameplayPlugin {
+    fn build(&self, app: &mut App) {
+        // Add GameState
+        app.init_state::<GameState>();
+        app.add_computed_state::<InGame>();
+
+        app.insert_resource(self.arena);
+
+        // Every random draw goes through the seeded `GameRng`, restarted for each run.
+        app.insert_resource(RngSeed(self.seed));
+        app.init_resource::<GameRng>();
+        app.add_systems(OnEnter(GameState::Game), reseed_game_rng);
+
+        // The whole simulation runs in `FixedUpdate` at this rate, so it plays out the
+        // same at any frame rate.
+        app.insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ));
+
+        // Every run starts from the same clocks, so it plays out the same no matter when it starts.
+        app.init_resource::<RunClock>();
+        app.init_resource::<AsteroidSpawnTimer>();
+        app.init_resource::<HealTimer>();
+        app.add_systems(OnEnter(InGame), start_run);
+        app.add_systems(
+            FixedUpdate,
+            tick_run_clock.run_if(in_state(InGame).or(in_state(GameState::GameOver))),
+        );
+
+        // Track the player's score.
+        app.insert_resource(PlayerScore(0));
+        app.add_event::<ScoreEvent>();
+        app.add_systems(OnEnter(InGame), restart_score);
+        app.add_systems(
+            FixedUpdate,
+            (tally_score)
+                .after(resolve_bullet_hits)
+                .run_if(in_state(InGame)),
+        );
+
+        // Keep track of how the player does over a run.
+        app.init_resource::<RunStats>();
+        app.add_systems(OnEnter(InGame), restart_stats);
+        app.add_systems(OnExit(InGame), finish_stats);
+
+        // The player ship speeds up with thrust and slows down with reverse thrust.
+        app.init_resource::<ShipConfig>();
+
+        // The player has a few ships, and earns more as their score goes up.
+        app.init_resource::<LivesConfig>();
+        app.init_resource::<PlayerLives>();
+        app.init_resource::<RespawnTimer>();
+        app.add_systems(OnEnter(InGame), restart_lives);
+        app.add_systems(
+            FixedUpdate,
+            (
+                award_extra_lives.after(tally_score),
+                respawn_player.after(resolve_ship_hits),
+                // The ship can be hit from the tick its invulnerability runs out.
+                update_invulnerability.before(check_ship_collisions),
+            )
+                .run_if(in_state(InGame)),
+        );
+        app.add_systems(Update, flash_invulnerable_player.run_if(in_state(InGame)));
+
+        // Setup the bullet and explosion resources, and load every sound up front.
+        app.add_systems(PostStartup, (setup_explosions, load_audio_assets));
+        app.add_systems(Update, validate_audio_assets);
+        app.add_event::<DuckMusic>();
+
         // Main Menu -> Game
-        app.add_systems(OnEnter(GameState::Game), setup_player);
+        app.add_systems(OnEnter(InGame), setup_player);
+
+        // The ship is flown by the player's actions in a live run, and by a replay in
+        // `GameState::Replay`.
+        app.add_plugins(ControlsPlugin);
+        app.init_resource::<ShipActions>();
+        app.add_systems(
+            PreUpdate,
+            read_ship_input
+                .after(resolve_actions)
+                .after(resolve_analog_input)
+                .after(resolve_cursor_position)
+                // The pause menu's buttons shouldn't fly the ship once the run resumes.
+                .run_if(in_state(GameState::Game).and(in_state(PauseState::Running))),
+        );
+        app.add_plugins(ReplayPlugin);
 
         // Game -> Game Over
 
         // Keep asteroids and bullets around for the game over screen.
         app.add_systems(
             OnExit(GameState::GameOver),
-            (despawn_asteroids, despawn_bullets),
+            (despawn_asteroids, despawn_bullets, despawn_player),
         );
 
-        // Add the camera and main song systems. This has to be done after the
-        // Startup stage, otherwise the loading of assets will break WASM builds.
-        app.add_systems(PostStartup, (spawn_camera, play_main_song));
+        // A run can also be abandoned from the pause menu.
+        app.add_plugins(PausePlugin);
+        app.add_systems(
+            OnEnter(GameState::MainMenu),
+            (despawn_asteroids, despawn_bullets, despawn_player),
+        );
 
         // Game systems that run until the game is over.
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
                 // Player ship
-                check_ship_bounds,
                 player_input_and_movement,
+                check_ship_bounds,
+            )
+                .chain()
+                .after(move_asteroids)
+                .run_if(in_state(InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            // Shots are resolved first, so an asteroid that was just shot can't hit the ship.
+            (
                 check_ship_collisions,
-                color_player,
+                resolve_ship_hits.after(resolve_bullet_hits),
             )
-                .run_if(in_state(GameState::Game)),
+                .chain()
+                .after(rebuild_asteroid_grid)
+                .run_if(in_state(InGame)),
         );
+        app.add_systems(Update, (color_player).run_if(in_state(InGame)));
 
         app.add_plugins(BulletPlugin);
 
+        // Cache collision outlines, and look up nearby asteroids instead of checking every one of them.
+        app.add_plugins(CollisionPlugin);
+
         // Game systems that run regardless of the game state. Allows for an interactive game over screen.
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
+                // Only spawn asteroids if we aren't in the main menu.
+                spawn_asteroids.after(tick_run_clock),
                 // Asteroids
                 move_asteroids,
                 check_asteroid_bounds,
+            )
+                .chain()
+                .run_if(in_state(InGame).or(in_state(GameState::GameOver))),
+        );
+        app.add_systems(
+            Update,
+            (
                 // Explosions
                 explosion_system,
             )
-                .run_if(in_state(GameState::Game).or(in_state(GameState::GameOver))),
+                .run_if(in_state(InGame).or(in_state(GameState::GameOver))),
         );
 
-        // Fixed systems
+        // Only run the heal player system if the game is in progress. It heals before the ship
+        // can be hit, so a replay heals and damages the ship in the same order as its run.
         app.add_systems(
             FixedUpdate,
-            (
-                spawn_asteroids
-                    .before(move_asteroids)
-                    .before(check_asteroid_bounds)
-                    // Only spawn asteroids if we aren't in the main menu.
-                    .run_if(in_state(GameState::Game).or(in_state(GameState::GameOver))),
-                // Only run the heal player system if the game is in progress.
-                (heal_player).run_if(in_state(GameState::Game)),
-            ),
+            heal_player
+                .before(check_ship_collisions)
+                .run_if(in_state(InGame)),
         );
     }
 }
 
+/// Runs the gameplay on `MinimalPlugins`, without a window, renderer or audio output.
+///
+/// The game starts directly in `GameState::Game` and time advances by a fixed `timestep`
+/// every `App::update`, so a simulation is independent of how fast the host machine is.
+/// Player input can be simulated by pressing keys on the `ButtonInput<KeyCode>` resource,
+/// or a recorded `replay` can be played back instead.
+///
+/// ```no_run
+/// use asteroids::AsteroidsHeadlessPlugin;
+/// use bevy::prelude::*;
+///
+/// let mut app = App::new();
+/// app.add_plugins(AsteroidsHeadlessPlugin::default());
+///
+/// for _ in 0..10_000 {
+///     app.update();
+/// }
+/// ```
+pub struct AsteroidsHeadlessPlugin {
+    /// The size of the arena the game is played in.
+    pub arena: Arena,
+    /// The seed every run starts from. `None` picks a new seed for every run.
+    pub seed: Option<u64>,
+    /// How much time passes on every `App::update`.
+    pub timestep: Duration,
+    /// A replay to play back instead of starting a live run.
+    pub replay: Option<Replay>,
+}
+
+impl Default for AsteroidsHeadlessPlugin {
+    fn default() -> Self {
+        Self {
+            arena: Arena::default(),
+            seed: None,
+            timestep: Duration::from_secs_f64(1.0 / SIMULATION_HZ),
+            replay: None,
+        }
+    }
+}
+
+impl Plugin for AsteroidsHeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            InputPlugin,
+            AssetPlugin::default(),
+        ));
+
+        // The gameplay creates meshes, materials and sounds even when nothing draws or plays them.
+        app.init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<AudioSource>()
+            .init_asset::<Pitch>()
+            .init_asset_loader::<AudioLoader>();
+
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(self.timestep));
+
+        // Skip the main menu, there is nobody to press start.
+        match &self.replay {
+            Some(replay) => {
+                // Replays have to start from outside of a run.
+                let replay = replay.clone();
+                app.insert_state(GameState::MainMenu);
+                app.add_systems(PostStartup, move |world: &mut World| {
+                    start_replay(world, replay.clone());
+                });
+            }
+            None => {
+                app.insert_state(GameState::Game);
+            }
+        }
+
+        app.add_plugins(AsteroidsGameplayPlugin {
+            arena: self.arena,
+            seed: self.seed,
+        });
+
+        app.add_systems(Last, despawn_audio_players);
+    }
+}
+
+/// Despawns every sound that was requested, as there is no audio output to play it on.
+fn despawn_audio_players(mut commands: Commands, query: Query<Entity, With<AudioPlayer>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
 /// The state of the user interface.
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GameState {
+    /// The game's assets are loading.
     #[default]
+    Loading,
     MainMenu,
     Game,
+    /// A recorded run is being played back. See [`replay::start_replay`].
+    Replay,
     GameOver,
+    /// The high score table is shown.
+    HighScores,
+}
+
+/// Exists while a run is being played, either live or from a replay.
+///
+/// Gameplay systems run in this state rather than `GameState::Game`, so that
+/// replays are simulated by exactly the same systems as live runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(sources: GameState) -> Option<Self> {
+        matches!(sources, GameState::Game | GameState::Replay).then_some(InGame)
+    }
+}
+
+/// How much gameplay time has passed since the current run started.
+#[derive(Resource, Debug, Default)]
+pub struct RunClock(pub Stopwatch);
+
+/// How many fixed ticks the simulation runs per second.
+pub const SIMULATION_HZ: f64 = 60.0;
+
+/// Resets the clocks and timers the gameplay depends on at the start of a run.
+///
+/// # Arguments
+/// * `run_clock`: The `RunClock` resource to reset.
+/// * `spawn_timer`: The `AsteroidSpawnTimer` resource to reset.
+/// * `heal_timer`: The `HealTimer` resource to reset.
+/// * `fixed_time`: The `Time<Fixed>` resource to discard leftover time from before the run.
+fn start_run(
+    mut run_clock: ResMut<RunClock>,
+    mut spawn_timer: ResMut<AsteroidSpawnTimer>,
+    mut heal_timer: ResMut<HealTimer>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    run_clock.0.reset();
+    spawn_timer.0.reset();
+    heal_timer.0.reset();
+
+    let overstep = fixed_time.overstep();
+    fixed_time.discard_overstep(overstep);
+}
+
+/// Abandons the current run and starts a new live run on the same frame.
+///
+/// Entering `GameState::Game` while already in it doesn't run any of the `OnEnter`
+/// systems, so the run goes through the main menu to clean up and start over.
+/// Call this through `Commands::queue` from a system.
+///
+/// # Arguments
+/// * `world`: The `World` to restart the run in.
+pub fn restart_run(world: &mut World) {
+    for state in [GameState::MainMenu, GameState::Game] {
+        world.resource_mut::<NextState<GameState>>().set(state);
+        world.run_schedule(StateTransition);
+    }
+}
+
+/// Advances the `RunClock` by a fixed tick.
+///
+/// # Arguments
+/// * `run_clock`: The `RunClock` resource to advance.
+/// * `time`: The `Time` resource to get the tick's duration.
+fn tick_run_clock(mut run_clock: ResMut<RunClock>, time: Res<Time>) {
+    run_clock.0.tick(time.delta());
 }
 
 fn spawn_camera(mut commands: Commands) {
-    commands.spawn((Name::new("Camera"), Camera2d));
+    // The camera hears the sound effects, see `audio::spatial`.
+    commands.spawn((Name::new("Camera"), Camera2d, SpatialListener::default()));
 }
 
 /// Checks if two lines intersect.
@@ -163,29 +505,124 @@ pub fn lines_intersect(p1: Vec2, p2: Vec2, p3: Vec2, p4: Vec2) -> Option<Vec2> {
     }
 }
 
-/// Converts a mesh and its transform into a list of points in absolute coordinates.
+/// Checks if a point is inside of a polygon, using the even-odd rule.
 ///
 /// # Arguments
-/// * `mesh`: The `Mesh` to convert.
-/// * `transform`: The `Transform` to apply to the mesh points.
+/// * `point`: The point to check.
+/// * `polygon`: The points of the polygon's outline. The outline is closed from the last
+///   point back to the first, so it may or may not repeat the first point at the end.
 ///
 /// # Returns
-/// A `Vec<Vec2>` containing the transformed, absolute points of the mesh.
-pub fn mesh_and_transform_to_points(mesh: &Mesh, transform: &Transform) -> Vec<Vec2> {
-    let position_data = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap();
-
-    if let VertexAttributeValues::Float32x3(positions) = position_data {
-        // Calculate the new position of the points from the transform
-        positions
-            .iter()
-            .map(|position| {
-                let curr_position = Vec3::from((position[0], position[1], position[2]));
-                let translated_position = transform.compute_matrix() * curr_position.extend(1.0);
-
-                Vec2::new(translated_position.x, translated_position.y)
+/// `true` if the point is inside the polygon, or `false` if it is not.
+pub fn point_in_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    let mut inside = false;
+
+    // Cast a ray to the right of the point and count how many edges it crosses.
+    let closing_edge = polygon.last().zip(polygon.first());
+    let edges = polygon
+        .windows(2)
+        .map(|edge| (&edge[0], &edge[1]))
+        .chain(closing_edge);
+
+    for (start, end) in edges {
+        if (start.y > point.y) != (end.y > point.y) {
+            let crossing_x = start.x + (point.y - start.y) / (end.y - start.y) * (end.x - start.x);
+
+            if point.x < crossing_x {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: &[Vec2] = &[
+        Vec2::new(0.0, 0.0),
+        Vec2::new(10.0, 0.0),
+        Vec2::new(10.0, 10.0),
+        Vec2::new(0.0, 10.0),
+    ];
+
+    #[test]
+    fn finds_points_inside_and_outside_a_polygon() {
+        assert!(point_in_polygon(Vec2::new(5.0, 5.0), SQUARE));
+        assert!(!point_in_polygon(Vec2::new(15.0, 5.0), SQUARE));
+        assert!(!point_in_polygon(Vec2::new(-5.0, 5.0), SQUARE));
+        assert!(!point_in_polygon(Vec2::new(5.0, 15.0), SQUARE));
+    }
+
+    #[test]
+    fn closes_the_outline_whether_or_not_it_repeats_the_first_point() {
+        let closed = [SQUARE, &SQUARE[..1]].concat();
+
+        for point in [Vec2::new(5.0, 5.0), Vec2::new(15.0, 5.0)] {
+            assert_eq!(
+                point_in_polygon(point, SQUARE),
+                point_in_polygon(point, &closed)
+            );
+        }
+    }
+
+    #[test]
+    fn counts_a_ray_through_a_vertex_once() {
+        let diamond = [
+            Vec2::new(0.0, -10.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(0.0, 10.0),
+            Vec2::new(-10.0, 0.0),
+        ];
+
+        // The ray to the right passes through both the left and right vertices.
+        assert!(point_in_polygon(Vec2::ZERO, &diamond));
+        assert!(!point_in_polygon(Vec2::new(-20.0, 0.0), &diamond));
+        assert!(!point_in_polygon(Vec2::new(20.0, 0.0), &diamond));
+    }
+
+    #[test]
+    fn a_shared_vertex_is_inside_exactly_one_polygon() {
+        // Four squares meet at (10, 10), which has to be inside one of them, but only one.
+        let containing = [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE]
+            .into_iter()
+            .filter(|corner| {
+                let square = SQUARE
+                    .iter()
+                    .map(|point| *point + *corner * 10.0)
+                    .collect::<Vec<_>>();
+                point_in_polygon(Vec2::splat(10.0), &square)
             })
-            .collect::<Vec<Vec2>>()
-    } else {
-        panic!("No positions found in the mesh");
+            .count();
+
+        assert_eq!(containing, 1);
+    }
+
+    #[test]
+    fn finds_where_lines_cross() {
+        let crossing = lines_intersect(
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, -1.0),
+            Vec2::new(0.0, 1.0),
+        );
+        assert_eq!(crossing, Some(Vec2::ZERO));
+
+        // Parallel lines, and lines that would only cross if they were longer.
+        assert_eq!(
+            lines_intersect(Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE),
+            None
+        );
+        assert_eq!(
+            lines_intersect(
+                Vec2::ZERO,
+                Vec2::X,
+                Vec2::new(2.0, -1.0),
+                Vec2::new(2.0, 1.0)
+            ),
+            None
+        );
     }
 }
@@ -2,8 +2,47 @@
 
 use bevy::prelude::*;
 
-use asteroids::*;
+use asteroids::{
+    arena::{Arena, EdgeMode},
+    replay::{Replay, ReplaySettings, start_replay},
+    *,
+};
 
 fn main() -> AppExit {
-    App::new().add_plugins(AsteroidsPlugin).run()
+    let mut app = App::new();
+    app.add_plugins(AsteroidsPlugin);
+
+    // `--record <path>` saves a replay of every run, `--replay <path>` plays one back,
+    // and `--wrap` makes objects wrap around the edges of the screen.
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--wrap" => {
+                app.world_mut().resource_mut::<Arena>().edge_mode = EdgeMode::Wrap;
+            }
+            "--record" => match args.next() {
+                Some(path) => {
+                    app.insert_resource(ReplaySettings {
+                        record_path: Some(path.into()),
+                    });
+                }
+                None => log::warn!("`--record` needs a path"),
+            },
+            "--replay" => match args.next() {
+                Some(path) => match Replay::load(&path) {
+                    Ok(replay) => {
+                        // Play it once every sound has loaded.
+                        app.add_systems(OnExit(GameState::Loading), move |world: &mut World| {
+                            start_replay(world, replay.clone());
+                        });
+                    }
+                    Err(error) => log::error!("Failed to load replay {path}: {error}"),
+                },
+                None => log::warn!("`--replay` needs a path"),
+            },
+            _ => log::warn!("Ignoring unknown argument `{arg}`"),
+        }
+    }
+
+    app.run()
 }
@@ -0,0 +1,18 @@
+//! Data for pausing a run.
+
+use bevy::prelude::*;
+
+use crate::InGame;
+
+/// Whether the run being played is paused. Only exists while `InGame`.
+#[derive(SubStates, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[source(InGame = InGame)]
+pub enum PauseState {
+    #[default]
+    Running,
+    /// The simulation is frozen and the pause menu is shown.
+    Paused,
+}
+
+/// How loud the music plays while the game is paused, relative to its normal volume.
+pub const PAUSED_MUSIC_VOLUME: f32 = 0.3;
@@ -0,0 +1,32 @@
+//! # Pause
+//!
+//! This module pauses and resumes a run.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
+
+use crate::{InGame, ui::SettingsState};
+use bevy::prelude::*;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<PauseState>();
+
+        // `Escape` closes the settings menus instead while they are open.
+        let settings_open = |state: Option<Res<State<SettingsState>>>| {
+            state.is_some_and(|state| state.get().is_open())
+        };
+        app.add_systems(
+            Update,
+            toggle_pause.run_if(in_state(InGame).and(not(settings_open))),
+        );
+
+        // The state is removed when a run ends while paused, which also exits `Paused`.
+        app.add_systems(OnEnter(PauseState::Paused), freeze_time);
+        app.add_systems(OnExit(PauseState::Paused), unfreeze_time);
+    }
+}
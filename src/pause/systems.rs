@@ -0,0 +1,89 @@
+//! Systems for pausing a run.
+
+use bevy::{prelude::*, window::WindowFocused};
+
+use super::PauseState;
+use crate::{
+    audio::{AudioBus, AudioChannel, SoundSink},
+    controls::{ActionState, PlayerAction},
+};
+
+/// Pauses or resumes the run when the pause action is pressed.
+///
+/// # Arguments
+/// * `actions`: The `ActionState` resource to check for the pause action.
+/// * `pause_state`: The current `PauseState`.
+/// * `next_state`: The `NextState<PauseState>` resource to switch to.
+pub fn toggle_pause(
+    actions: Res<ActionState>,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if actions.just_pressed(PlayerAction::Pause) {
+        next_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+/// Pauses the run when the window loses focus, e.g. when the player switches to another window.
+///
+/// # Arguments
+/// * `focus_events`: The `EventReader` for changes in window focus.
+/// * `next_state`: The `NextState<PauseState>` resource to pause with.
+pub fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if focus_events.read().any(|event| !event.focused) {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+/// Freezes the simulation by pausing virtual time.
+///
+/// `FixedUpdate` only runs as virtual time passes, so every gameplay system stops, and the
+/// timers in `Update` stop counting down.
+///
+/// # Arguments
+/// * `time`: The `Time<Virtual>` resource to pause.
+pub fn freeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+/// Lets the simulation carry on from where it was paused.
+///
+/// # Arguments
+/// * `time`: The `Time<Virtual>` resource to unpause.
+pub fn unfreeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// Pauses the sound effects while the game is paused. `mix_audio` turns the music down.
+///
+/// # Arguments
+/// * `sinks`: A query that retrieves every playing sound and its `AudioChannel`.
+pub fn pause_sound_effects(mut sinks: Query<(SoundSink, &AudioChannel)>) {
+    for (mut sink, channel) in sinks.iter_mut() {
+        if let Some(sink) = sink.playback()
+            && channel.bus == AudioBus::Effects
+        {
+            sink.pause();
+        }
+    }
+}
+
+/// Carries on playing the sound effects when the game is resumed.
+///
+/// # Arguments
+/// * `sinks`: A query that retrieves every playing sound and its `AudioChannel`.
+pub fn resume_sound_effects(mut sinks: Query<(SoundSink, &AudioChannel)>) {
+    for (mut sink, channel) in sinks.iter_mut() {
+        if let Some(sink) = sink.playback()
+            && channel.bus == AudioBus::Effects
+        {
+            sink.play();
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Data for moving entities around the arena.
+
+use bevy::prelude::*;
+
+/// How fast, and in which direction, an entity moves, in units per second.
+///
+/// The player ship, bullets and asteroids all keep moving at their velocity until
+/// something changes it, so the ship drifts the way it was going while it turns.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Velocity(pub Vec2);
+
+impl Velocity {
+    /// Moves a transform along the velocity.
+    ///
+    /// # Arguments
+    /// * `transform`: The `Transform` to move.
+    /// * `delta`: How long it moves for, in seconds.
+    pub fn advance(self, transform: &mut Transform, delta: f32) {
+        transform.translation += self.0.extend(0.0) * delta;
+    }
+}
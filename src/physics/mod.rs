@@ -0,0 +1,6 @@
+//! # Physics
+//!
+//! This module contains the motion shared by everything that flies around the arena.
+
+pub mod data;
+pub use data::*;
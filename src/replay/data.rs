@@ -0,0 +1,314 @@
+//! Data for recording and replaying runs, and the replay file format.
+//!
+//! A replay file is plain text. The header holds the format version, the seed the run
+//! started from, the fixed timestep, and how many fixed ticks the run lasted. It is
+//! followed by one line per tick with the player's [`ShipActions`]: the thrust, negative
+//! for reverse, the turn, and `1` if the ship fired or `0` if it didn't:
+//!
+//! ```text
+//! asteroids-replay 3
+//! seed 1234
+//! timestep 16666666
+//! ticks 3
+//! 0 0 0
+//! 127 -64 0
+//! 127 0 1
+//! ```
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::ship::ShipActions;
+
+/// The first word of every replay file.
+pub const REPLAY_MAGIC: &str = "asteroids-replay";
+
+/// The version of the replay file format that is written.
+pub const REPLAY_VERSION: u32 = 3;
+
+/// A recorded run that can be played back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Replay {
+    /// The seed the run's `GameRng` started from.
+    pub seed: u64,
+    /// The fixed timestep the run was simulated with.
+    pub timestep: Duration,
+    /// The actions the player took on every fixed tick of the run, in order.
+    pub ticks: Vec<ShipActions>,
+}
+
+impl Replay {
+    /// Writes the replay in the replay file format.
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "{REPLAY_MAGIC} {REPLAY_VERSION}")?;
+        writeln!(writer, "seed {}", self.seed)?;
+        writeln!(writer, "timestep {}", self.timestep.as_nanos())?;
+        writeln!(writer, "ticks {}", self.ticks.len())?;
+
+        for actions in &self.ticks {
+            writeln!(
+                writer,
+                "{} {} {}",
+                actions.thrust,
+                actions.turn,
+                u8::from(actions.fire)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a replay written by [`Replay::write`].
+    pub fn read(reader: impl BufRead) -> Result<Self, ReplayError> {
+        let mut lines = reader.lines().enumerate();
+
+        // Reads the next line, failing if the file ends early.
+        let mut next_line = || match lines.next() {
+            Some((index, line)) => Ok((index + 1, line?)),
+            None => Err(ReplayError::Parse {
+                line: 0,
+                message: "unexpected end of file".to_string(),
+            }),
+        };
+
+        let (line, magic) = next_line()?;
+        match magic.split_once(' ') {
+            Some((REPLAY_MAGIC, version)) if version == REPLAY_VERSION.to_string() => {}
+            Some((REPLAY_MAGIC, version)) => {
+                return Err(ReplayError::parse(
+                    line,
+                    format!("unsupported version {version}"),
+                ));
+            }
+            _ => return Err(ReplayError::parse(line, "not a replay file")),
+        }
+
+        let seed = parse_header(next_line()?, "seed")?;
+        let timestep = Duration::from_nanos(parse_header(next_line()?, "timestep")?);
+        let tick_count: usize = parse_header(next_line()?, "ticks")?;
+
+        let mut ticks = Vec::with_capacity(tick_count);
+
+        for _ in 0..tick_count {
+            let (line, text) = next_line()?;
+            ticks.push(parse_actions(line, &text)?);
+        }
+
+        Ok(Self {
+            seed,
+            timestep,
+            ticks,
+        })
+    }
+
+    /// Saves the replay to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a replay from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Parses a `name value` header line.
+fn parse_header<T: std::str::FromStr>(
+    (line, text): (usize, String),
+    name: &str,
+) -> Result<T, ReplayError> {
+    match text.split_once(' ') {
+        Some((key, value)) if key == name => parse_number(line, value),
+        _ => Err(ReplayError::parse(line, format!("expected `{name}`"))),
+    }
+}
+
+/// Parses a `thrust turn fire` tick line.
+fn parse_actions(line: usize, text: &str) -> Result<ShipActions, ReplayError> {
+    let mut values = text.split_whitespace();
+    let mut next_value = || {
+        values
+            .next()
+            .ok_or_else(|| ReplayError::parse(line, "expected `thrust turn fire`"))
+    };
+
+    let thrust: i8 = parse_number(line, next_value()?)?;
+    let turn: i8 = parse_number(line, next_value()?)?;
+    let fire: u8 = parse_number(line, next_value()?)?;
+
+    if thrust < -ShipActions::FULL || turn < -ShipActions::FULL || fire > 1 {
+        return Err(ReplayError::parse(
+            line,
+            format!("invalid actions `{text}`"),
+        ));
+    }
+
+    Ok(ShipActions {
+        thrust,
+        turn,
+        fire: fire == 1,
+    })
+}
+
+/// Parses a number on the given line.
+fn parse_number<T: std::str::FromStr>(line: usize, text: &str) -> Result<T, ReplayError> {
+    text.trim()
+        .parse()
+        .map_err(|_| ReplayError::parse(line, format!("invalid number `{text}`")))
+}
+
+/// An error from reading a replay file.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file isn't a valid replay.
+    Parse {
+        /// The line the error is on, starting at 1.
+        line: usize,
+        /// What is wrong with the line.
+        message: String,
+    },
+}
+
+impl ReplayError {
+    fn parse(line: usize, message: impl Into<String>) -> Self {
+        Self::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "failed to read replay: {error}"),
+            ReplayError::Parse { line, message } => {
+                write!(f, "invalid replay on line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Settings for recording runs.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ReplaySettings {
+    /// Where to save the replay of every live run. `None` keeps it in memory only.
+    pub record_path: Option<PathBuf>,
+}
+
+/// Records the player's actions during a live run.
+///
+/// The recording of the last run stays here until the next run starts.
+#[derive(Resource, Debug, Default)]
+pub struct ReplayRecorder {
+    /// The run recorded so far.
+    pub replay: Replay,
+}
+
+/// A replay being played back in `GameState::Replay`.
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    /// The replay being played.
+    pub replay: Replay,
+    /// The index of the next tick to play.
+    pub tick: usize,
+}
+
+impl ReplayPlayback {
+    /// Creates a playback of a replay, starting at its first tick.
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, tick: 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> Result<Replay, ReplayError> {
+        Replay::read(text.as_bytes())
+    }
+
+    #[test]
+    fn round_trips_through_the_file_format() {
+        let replay = Replay {
+            seed: 1234,
+            timestep: Duration::from_nanos(16_666_666),
+            ticks: vec![
+                ShipActions::default(),
+                ShipActions::from_amounts(1.0, -0.5, false),
+                ShipActions::from_amounts(-1.0, 1.0, true),
+            ],
+        };
+
+        let mut file = Vec::new();
+        replay.write(&mut file).unwrap();
+
+        assert_eq!(read(std::str::from_utf8(&file).unwrap()).unwrap(), replay);
+    }
+
+    #[test]
+    fn writes_the_documented_format() {
+        let replay = Replay {
+            seed: 1234,
+            timestep: Duration::from_nanos(16_666_666),
+            ticks: vec![ShipActions {
+                thrust: 127,
+                turn: -64,
+                fire: true,
+            }],
+        };
+
+        let mut file = Vec::new();
+        replay.write(&mut file).unwrap();
+
+        assert_eq!(
+            String::from_utf8(file).unwrap(),
+            "asteroids-replay 3\nseed 1234\ntimestep 16666666\nticks 1\n127 -64 1\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let line_of = |text| match read(text) {
+            Err(ReplayError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {other:?}"),
+        };
+
+        assert_eq!(line_of("not-a-replay 3\n"), 1);
+        assert_eq!(line_of("asteroids-replay 99\n"), 1);
+        assert_eq!(line_of("asteroids-replay 3\nsalt 1\n"), 2);
+        assert_eq!(
+            line_of("asteroids-replay 3\nseed 1\ntimestep 1\nticks 1\n-128 0 0\n"),
+            5
+        );
+        assert_eq!(
+            line_of("asteroids-replay 3\nseed 1\ntimestep 1\nticks 1\n0 0 2\n"),
+            5
+        );
+        // The file ends before every tick was read.
+        assert_eq!(
+            line_of("asteroids-replay 3\nseed 1\ntimestep 1\nticks 2\n0 0 0\n"),
+            0
+        );
+    }
+}
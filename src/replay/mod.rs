@@ -0,0 +1,43 @@
+//! # Replay
+//!
+//! This module records the player's actions during a run and plays them back.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
+
+use crate::{GameState, rng::reseed_game_rng, ship::player_input_and_movement};
+use bevy::prelude::*;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplaySettings>();
+        app.init_resource::<ReplayRecorder>();
+
+        // Recording a live run
+        app.add_systems(
+            OnEnter(GameState::Game),
+            start_recording.after(reseed_game_rng),
+        );
+        app.add_systems(
+            FixedUpdate,
+            record_tick
+                .before(player_input_and_movement)
+                .run_if(in_state(GameState::Game).and(run_is_going)),
+        );
+        app.add_systems(OnExit(GameState::Game), save_recording);
+
+        // Playing a replay back
+        app.add_systems(OnEnter(GameState::Replay), start_playback);
+        app.add_systems(
+            FixedUpdate,
+            feed_replay_tick
+                .before(player_input_and_movement)
+                .run_if(in_state(GameState::Replay).and(run_is_going)),
+        );
+        app.add_systems(OnExit(GameState::Replay), finish_playback);
+    }
+}
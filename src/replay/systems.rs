@@ -0,0 +1,162 @@
+//! Systems for recording and replaying runs.
+
+use bevy::prelude::*;
+
+use super::{Replay, ReplayPlayback, ReplayRecorder, ReplaySettings};
+use crate::{GameState, InGame, rng::GameRng, ship::ShipActions};
+
+/// Starts playing back a replay from the next frame on.
+///
+/// A replay can't be started in the middle of a run, only from outside of `InGame`, and
+/// not before the game has finished loading, e.g. from `OnExit(GameState::Loading)`.
+/// Call this through `Commands::queue` from a system.
+///
+/// # Arguments
+/// * `world`: The `World` to play the replay in.
+/// * `replay`: The `Replay` to play.
+pub fn start_replay(world: &mut World, replay: Replay) {
+    if world.get_resource::<State<InGame>>().is_some() {
+        log::error!("Can't start a replay in the middle of a run");
+        return;
+    }
+
+    if world
+        .get_resource::<State<GameState>>()
+        .is_some_and(|state| *state.get() == GameState::Loading)
+    {
+        log::error!("Can't start a replay before the game has loaded");
+        return;
+    }
+
+    world.insert_resource(ReplayPlayback::new(replay));
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Replay);
+}
+
+/// Run condition that is true until the run has ended on an earlier tick of this frame.
+///
+/// States only change once per frame, so a few more fixed ticks can run after the ship
+/// is destroyed. Those ticks aren't part of the run, as there may be none of them when
+/// the run is played back at a different frame rate.
+pub fn run_is_going(next_state: Res<NextState<GameState>>) -> bool {
+    matches!(*next_state, NextState::Unchanged)
+}
+
+/// Starts a new recording at the start of a live run.
+///
+/// # Arguments
+/// * `recorder`: The `ReplayRecorder` resource to start recording in.
+/// * `rng`: The `GameRng` resource to get the run's seed from.
+/// * `fixed_time`: The `Time<Fixed>` resource to get the fixed timestep from.
+pub fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    recorder.replay = Replay {
+        seed: rng.seed(),
+        timestep: fixed_time.timestep(),
+        ticks: Vec::new(),
+    };
+}
+
+/// Records the player's actions for this tick.
+///
+/// # Arguments
+/// * `recorder`: The `ReplayRecorder` resource to record the tick in.
+/// * `actions`: The `ShipActions` resource with the player's actions for this tick.
+pub fn record_tick(mut recorder: ResMut<ReplayRecorder>, actions: Res<ShipActions>) {
+    recorder.replay.ticks.push(*actions);
+}
+
+/// Saves the recording of a live run once it ends, if a `record_path` is set.
+///
+/// # Arguments
+/// * `recorder`: The `ReplayRecorder` resource with the finished recording.
+/// * `settings`: The `ReplaySettings` resource to get the path to save to.
+pub fn save_recording(recorder: Res<ReplayRecorder>, settings: Res<ReplaySettings>) {
+    let Some(path) = &settings.record_path else {
+        return;
+    };
+
+    match recorder.replay.save(path) {
+        Ok(()) => log::info!("Saved replay to {}", path.display()),
+        Err(error) => log::error!("Failed to save replay to {}: {error}", path.display()),
+    }
+}
+
+/// Restarts the `GameRng` from the replay's seed when a replay starts.
+///
+/// # Arguments
+/// * `playback`: The `ReplayPlayback` resource queued by [`start_replay`].
+/// * `rng`: The `GameRng` resource to restart.
+/// * `fixed_time`: The `Time<Fixed>` resource to check the fixed timestep against.
+/// * `next_state`: The `NextState<GameState>` resource to leave if there is nothing to play.
+pub fn start_playback(
+    playback: Option<Res<ReplayPlayback>>,
+    mut rng: ResMut<GameRng>,
+    fixed_time: Res<Time<Fixed>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(playback) = playback else {
+        log::warn!("Entered the replay state without a replay, use `start_replay`");
+        next_state.set(GameState::MainMenu);
+        return;
+    };
+
+    if playback.replay.timestep != fixed_time.timestep() {
+        log::warn!(
+            "Replay was recorded with a timestep of {:?} instead of {:?}, it will not play back the same",
+            playback.replay.timestep,
+            fixed_time.timestep()
+        );
+    }
+
+    *rng = GameRng::new(playback.replay.seed);
+}
+
+/// Feeds the recorded actions back in place of the player's input.
+///
+/// # Arguments
+/// * `playback`: The `ReplayPlayback` resource with the replay being played.
+/// * `actions`: The `ShipActions` resource to write the recorded actions to.
+/// * `next_state`: The `NextState<GameState>` resource to end the replay once it runs out.
+pub fn feed_replay_tick(
+    mut playback: ResMut<ReplayPlayback>,
+    mut actions: ResMut<ShipActions>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(recorded) = playback.replay.ticks.get(playback.tick).copied() else {
+        // The recorded run ended before this tick, but the replay didn't.
+        *actions = ShipActions::default();
+        next_state.set(GameState::GameOver);
+        return;
+    };
+
+    *actions = recorded;
+    playback.tick += 1;
+}
+
+/// Removes the `ReplayPlayback` once a replay ends.
+///
+/// # Arguments
+/// * `commands`: The `Commands` resource to remove the `ReplayPlayback` resource.
+/// * `playback`: The `ReplayPlayback` resource with the replay that was played.
+pub fn finish_playback(mut commands: Commands, playback: Option<Res<ReplayPlayback>>) {
+    let Some(playback) = playback else {
+        return;
+    };
+
+    // A replay that ends early has played out differently than it was recorded.
+    let recorded = playback.replay.ticks.len();
+    if playback.tick != recorded {
+        log::warn!(
+            "Replay desynced: the run ended after {} of {} ticks",
+            playback.tick,
+            recorded
+        );
+    }
+
+    commands.remove_resource::<ReplayPlayback>();
+}
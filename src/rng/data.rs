@@ -0,0 +1,111 @@
+//! Data for the seeded random number generators.
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The seed used to start every run.
+///
+/// `None` picks a fresh seed for every run, `Some` makes every run start from the same seed.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct RngSeed(pub Option<u64>);
+
+/// The source of every random draw in the gameplay.
+///
+/// Each subsystem draws from its own stream, so that e.g. generating a more detailed
+/// asteroid shape doesn't change where the next asteroid spawns. A seed plus the
+/// player's input is enough to reproduce a run.
+#[derive(Resource, Debug, Clone)]
+pub struct GameRng {
+    /// The seed all of the streams were created from.
+    seed: u64,
+    /// Picks when, where and how big new asteroids spawn.
+    pub spawning: ChaCha8Rng,
+    /// Generates the outline of new asteroids.
+    pub shapes: ChaCha8Rng,
+    /// Places the children of a destroyed asteroid.
+    pub splitting: ChaCha8Rng,
+}
+
+impl GameRng {
+    /// Creates a new set of streams from the given seed.
+    pub fn new(seed: u64) -> Self {
+        let stream = |stream| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream);
+            rng
+        };
+
+        Self {
+            seed,
+            spawning: stream(0),
+            shapes: stream(1),
+            splitting: stream(2),
+        }
+    }
+
+    /// Returns the seed the streams were created from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    /// Draws a few values from a stream, as the spawner would.
+    fn draw(rng: &mut ChaCha8Rng) -> Vec<f32> {
+        (0..8).map(|_| rng.random_range(0.0..1.0)).collect()
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_draws() {
+        let mut first = GameRng::new(1234);
+        let mut second = GameRng::new(1234);
+
+        assert_eq!(first.seed(), 1234);
+        assert_eq!(draw(&mut first.spawning), draw(&mut second.spawning));
+        assert_eq!(draw(&mut first.shapes), draw(&mut second.shapes));
+        assert_eq!(draw(&mut first.splitting), draw(&mut second.splitting));
+
+        assert_ne!(
+            draw(&mut GameRng::new(1234).spawning),
+            draw(&mut GameRng::new(1235).spawning)
+        );
+    }
+
+    #[test]
+    fn streams_do_not_affect_each_other() {
+        let mut untouched = GameRng::new(1234);
+        let mut used = GameRng::new(1234);
+
+        // Drawing lots of shapes and splits doesn't move the spawner along.
+        for _ in 0..100 {
+            draw(&mut used.shapes);
+            draw(&mut used.splitting);
+        }
+        assert_eq!(draw(&mut untouched.spawning), draw(&mut used.spawning));
+
+        // Nor does spawning move the shapes or splits along.
+        let mut untouched = GameRng::new(1234);
+        let mut used = GameRng::new(1234);
+        for _ in 0..100 {
+            draw(&mut used.spawning);
+        }
+        assert_eq!(draw(&mut untouched.shapes), draw(&mut used.shapes));
+        assert_eq!(draw(&mut untouched.splitting), draw(&mut used.splitting));
+
+        // The streams are different from each other.
+        let mut fresh = GameRng::new(1234);
+        assert_ne!(draw(&mut fresh.spawning), draw(&mut fresh.shapes));
+    }
+}
@@ -0,0 +1,8 @@
+//! # RNG
+//!
+//! This module contains the seeded random number generators used by the gameplay.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
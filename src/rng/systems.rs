@@ -0,0 +1,18 @@
+//! Systems for the seeded random number generators.
+
+use bevy::prelude::*;
+
+use super::{GameRng, RngSeed};
+
+/// Restarts the `GameRng` streams at the start of a run.
+///
+/// # Arguments
+/// * `rng`: The `GameRng` resource to restart.
+/// * `seed`: The `RngSeed` resource that decides which seed the run uses.
+pub fn reseed_game_rng(mut rng: ResMut<GameRng>, seed: Res<RngSeed>) {
+    let seed = seed.0.unwrap_or_else(rand::random);
+
+    log::info!("Starting run with seed {seed}");
+
+    *rng = GameRng::new(seed);
+}
@@ -2,6 +2,11 @@
 
 use bevy::{asset::RenderAssetUsages, prelude::*, render::mesh::PrimitiveTopology};
 
+use crate::{
+    arena::WrapGhosts, collision::CollisionPolygon, interpolation::TransformInterpolation,
+    physics::Velocity,
+};
+
 /// The points that define the player ship's shape.
 /// These point are turned into a `LineStrip` mesh for rendering.
 /// The ship itself is an arrow head shape.
@@ -13,13 +18,250 @@ pub const PLAYER_SHIP_POINTS: &[Vec3] = &[
     Vec3::new(-5.0, -5.0, 1.0),
 ];
 
-/// The player ship component that holds the player's health and speed.
+/// The radius of a circle around the ship's centre that contains every point of
+/// `PLAYER_SHIP_POINTS`, out to its rear corners.
+pub const PLAYER_SHIP_RADIUS: f32 = 5.0 * std::f32::consts::SQRT_2;
+
+/// The player ship component that holds the player's health.
+///
+/// The ship moves with its `Velocity`.
 #[derive(Component)]
 pub struct PlayerShip {
     /// The health of the player ship.
     pub health: i32,
-    /// The speed of the player ship.
-    pub speed: f32,
+}
+
+/// How often the player ship regains a point of health.
+#[derive(Resource, Debug)]
+pub struct HealTimer(pub Timer);
+
+impl Default for HealTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.5, TimerMode::Repeating))
+    }
+}
+
+/// How many lives the player gets, and how they lose and earn them.
+///
+/// Insert this resource before adding the gameplay plugin to change the defaults.
+#[derive(Resource, Debug, Clone)]
+pub struct LivesConfig {
+    /// The number of ships the player starts a run with.
+    pub starting_lives: u32,
+    /// The player earns an extra life every time their score passes a multiple of this.
+    /// Zero turns extra lives off.
+    pub extra_life_every: i32,
+    /// How long after losing a ship the next one can respawn, in seconds.
+    pub respawn_delay: f32,
+    /// How far from the centre every asteroid has to be before the ship respawns.
+    pub respawn_clearance: f32,
+    /// How long a respawned ship can't be hit, in seconds.
+    pub invulnerability: f32,
+}
+
+impl Default for LivesConfig {
+    fn default() -> Self {
+        Self {
+            starting_lives: 3,
+            extra_life_every: 100,
+            respawn_delay: 1.5,
+            respawn_clearance: 100.0,
+            invulnerability: 3.0,
+        }
+    }
+}
+
+/// How the player ship speeds up and slows down.
+///
+/// Thrust accelerates the ship the way it is facing, and it keeps drifting the way it was
+/// going while it turns.
+///
+/// Insert this resource before adding the gameplay plugin to change the defaults.
+#[derive(Resource, Debug, Clone)]
+pub struct ShipConfig {
+    /// The fastest the ship flies.
+    pub max_speed: f32,
+    /// The fastest reverse thrust pushes the ship backwards.
+    pub max_reverse_speed: f32,
+    /// How much full thrust speeds the ship up every second.
+    pub acceleration: f32,
+    /// How much full reverse thrust slows the ship down every second while it still flies
+    /// forwards.
+    pub braking: f32,
+    /// How much the ship slows down every second without thrust. Zero lets it drift
+    /// forever.
+    pub drag: f32,
+}
+
+impl Default for ShipConfig {
+    fn default() -> Self {
+        Self {
+            max_speed: 50.0,
+            max_reverse_speed: 20.0,
+            acceleration: 40.0,
+            braking: 80.0,
+            drag: 10.0,
+        }
+    }
+}
+
+impl ShipConfig {
+    /// Returns the ship's velocity after thrusting for a while.
+    ///
+    /// # Arguments
+    /// * `velocity`: The velocity of the ship.
+    /// * `facing`: The direction the ship is facing, of length 1.
+    /// * `thrust`: How hard the ship thrusts, from -1 for full reverse to 1 for full thrust.
+    /// * `delta`: How long the ship thrusts for, in seconds.
+    pub fn accelerate(&self, velocity: Vec2, facing: Vec2, thrust: f32, delta: f32) -> Vec2 {
+        let forward_speed = velocity.dot(facing);
+
+        let velocity = if thrust > 0.0 {
+            velocity + facing * self.acceleration * thrust * delta
+        } else if thrust < 0.0 && forward_speed > 0.0 {
+            // Brake, stopping rather than reversing on the tick the ship stops going forwards.
+            velocity - facing * (self.braking * -thrust * delta).min(forward_speed)
+        } else if thrust < 0.0 {
+            // Reverse, without pushing the ship backwards faster than the max reverse speed.
+            let headroom = (self.max_reverse_speed + forward_speed).max(0.0);
+            velocity - facing * (self.acceleration * -thrust * delta).min(headroom)
+        } else {
+            // Drift to a stop.
+            let speed = (velocity.length() - self.drag * delta).max(0.0);
+            velocity.normalize_or_zero() * speed
+        };
+
+        velocity.clamp_length_max(self.max_speed)
+    }
+}
+
+/// The lives the player has left in this run, including the ship currently in play.
+#[derive(Resource, Debug, Default)]
+pub struct PlayerLives {
+    /// The number of ships the player has left.
+    pub remaining: u32,
+    /// The score at which the player earns their next extra life.
+    pub next_extra_life: i32,
+}
+
+/// Counts down until the next ship can respawn after the player loses one.
+#[derive(Resource, Debug, Default)]
+pub struct RespawnTimer(pub Timer);
+
+/// Keeps a freshly respawned ship from being hit until the timer finishes.
+///
+/// The ship flashes while it is invulnerable.
+#[derive(Component, Debug)]
+pub struct Invulnerable(pub Timer);
+
+/// How far, in radians, the ship has to be from facing the cursor to turn at its full rate
+/// with mouse aim. Closer than this, it turns slower so that it doesn't overshoot.
+pub const AIM_SLOWDOWN_ANGLE: f32 = 0.25;
+
+/// Returns how fast to turn a ship to face a point, from -1 for clockwise to 1 for
+/// counter-clockwise, as for [`ShipActions::from_amounts`].
+///
+/// # Arguments
+/// * `transform`: Where the ship is and which way it faces.
+/// * `target`: The point to face.
+pub fn turn_towards(transform: &Transform, target: Vec2) -> f32 {
+    let offset = target - transform.translation.truncate();
+    if offset == Vec2::ZERO {
+        return 0.0;
+    }
+
+    let facing = transform.up().truncate();
+    (facing.angle_to(offset) / AIM_SLOWDOWN_ANGLE).clamp(-1.0, 1.0)
+}
+
+/// The actions the player takes with the ship on a single tick.
+///
+/// Gameplay reads these instead of the player's input, so a replay can feed back recorded
+/// actions. Analog input is stored in steps of `1 / ShipActions::FULL`, so that a replay
+/// plays back exactly what was recorded.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShipActions {
+    /// How hard to accelerate in the direction the ship is facing, from `-FULL` for full
+    /// reverse to `FULL` for full thrust.
+    pub thrust: i8,
+    /// How fast to rotate the ship, from `-FULL` for clockwise to `FULL` for counter-clockwise.
+    pub turn: i8,
+    /// Fire a bullet. Set when the fire button is pressed, until a tick fires the bullet.
+    pub fire: bool,
+}
+
+impl ShipActions {
+    /// The value of `thrust` and `turn` when they are all the way on.
+    pub const FULL: i8 = 127;
+
+    /// Creates the actions from analog amounts, rounded to the nearest step.
+    ///
+    /// # Arguments
+    /// * `thrust`: How hard to accelerate, from -1 for full reverse to 1 for full thrust.
+    /// * `turn`: How fast to rotate, from -1 for clockwise to 1 for counter-clockwise.
+    /// * `fire`: Whether to fire a bullet.
+    pub fn from_amounts(thrust: f32, turn: f32, fire: bool) -> Self {
+        let full = f32::from(Self::FULL);
+
+        Self {
+            thrust: (thrust.clamp(-1.0, 1.0) * full).round() as i8,
+            turn: (turn.clamp(-1.0, 1.0) * full).round() as i8,
+            fire,
+        }
+    }
+
+    /// Returns how hard to accelerate, from -1 for full reverse to 1 for full thrust.
+    pub fn thrust_amount(self) -> f32 {
+        f32::from(self.thrust) / f32::from(Self::FULL)
+    }
+
+    /// Returns how fast to rotate, from -1 for clockwise to 1 for counter-clockwise.
+    pub fn turn_amount(self) -> f32 {
+        f32::from(self.turn) / f32::from(Self::FULL)
+    }
+}
+
+impl PlayerShip {
+    /// Spawns a new player ship at full health in the centre of the arena.
+    ///
+    /// # Arguments
+    /// * `commands`: The `Commands` resource to spawn the player ship entity.
+    /// * `meshes`: The `Assets<Mesh>` resource to create the player ship mesh.
+    /// * `materials`: The `Assets<ColorMaterial>` resource to create the player ship material.
+    ///
+    /// # Returns
+    /// The `Entity` of the new player ship.
+    pub fn spawn_new(
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Entity {
+        // Create a mesh for the player ship
+        let ship_mesh = Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::all())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, PLAYER_SHIP_POINTS.to_vec());
+
+        // Spawn the player ship
+        commands
+            .spawn((
+                Name::new("Player Ship"),
+                PlayerShip { health: 100 },
+                Velocity::default(),
+                Mesh2d(meshes.add(ship_mesh)),
+                MeshMaterial2d(
+                    // Use a green color for the player ship in full health
+                    materials.add(ColorMaterial::from(Color::LinearRgba(LinearRgba::new(
+                        0.2, 1.0, 0.2, 1.0,
+                    )))),
+                ),
+                Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
+                CollisionPolygon::new(PLAYER_SHIP_POINTS),
+                TransformInterpolation::default(),
+                WrapGhosts {
+                    radius: PLAYER_SHIP_RADIUS,
+                },
+            ))
+            .id()
+    }
 }
 
 /// Sets up the player ship with a mesh and material.
@@ -33,26 +275,7 @@ pub fn setup_player(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    // Create a mesh for the player ship
-    let ship_mesh = Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::all())
-        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, PLAYER_SHIP_POINTS.to_vec());
-
-    // Spawn the player ship
-    commands.spawn((
-        Name::new("Player Ship"),
-        PlayerShip {
-            health: 100,
-            speed: 0.0,
-        },
-        Mesh2d(meshes.add(ship_mesh)),
-        MeshMaterial2d(
-            // Use a green color for the player ship in full health
-            materials.add(ColorMaterial::from(Color::LinearRgba(LinearRgba::new(
-                0.2, 1.0, 0.2, 1.0,
-            )))),
-        ),
-        Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
-    ));
+    PlayerShip::spawn_new(&mut commands, &mut meshes, &mut materials);
 }
 
 /// Deletes the player ship entity from the game.
@@ -65,3 +288,119 @@ pub fn despawn_player(mut commands: Commands, player_query: Query<Entity, With<P
         commands.entity(entity).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn the_ship_radius_reaches_its_furthest_point() {
+        let furthest = PLAYER_SHIP_POINTS
+            .iter()
+            .map(|point| point.truncate().length())
+            .fold(0.0, f32::max);
+
+        assert!((furthest - PLAYER_SHIP_RADIUS).abs() < 1e-4);
+    }
+
+    /// Thrusts for `ticks` fixed ticks, facing up.
+    fn thrust_for(config: &ShipConfig, mut velocity: Vec2, thrust: f32, ticks: usize) -> Vec2 {
+        for _ in 0..ticks {
+            velocity = config.accelerate(velocity, Vec2::Y, thrust, TICK);
+        }
+        velocity
+    }
+
+    #[test]
+    fn braking_stops_the_ship_without_reversing_it() {
+        let config = ShipConfig::default();
+        let mut velocity = Vec2::new(0.0, 30.0);
+
+        // Brake hard enough to stop several times over on a single long tick.
+        velocity = config.accelerate(velocity, Vec2::Y, -1.0, 10.0);
+        assert_eq!(velocity, Vec2::ZERO);
+
+        // A tick at a time, the ship slows down without ever going backwards.
+        let mut velocity = Vec2::new(0.0, 30.0);
+        while velocity.y > 0.0 {
+            let braked = config.accelerate(velocity, Vec2::Y, -1.0, TICK);
+            assert!(braked.y >= 0.0 && braked.y < velocity.y);
+            velocity = braked;
+        }
+    }
+
+    #[test]
+    fn braking_keeps_the_sideways_drift() {
+        let config = ShipConfig::default();
+
+        let velocity = config.accelerate(Vec2::new(10.0, 30.0), Vec2::Y, -1.0, 10.0);
+        assert_eq!(velocity, Vec2::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn reverse_thrust_is_limited_to_the_max_reverse_speed() {
+        let config = ShipConfig::default();
+
+        let top_speed = Vec2::new(0.0, -config.max_reverse_speed);
+
+        let velocity = thrust_for(&config, Vec2::ZERO, -1.0, 600);
+        assert!(velocity.abs_diff_eq(top_speed, 1e-4), "{velocity}");
+
+        // Half reverse reaches the same speed, only slower.
+        let velocity = thrust_for(&config, Vec2::ZERO, -0.5, 600);
+        assert!(velocity.abs_diff_eq(top_speed, 1e-4), "{velocity}");
+
+        // A ship already going backwards faster isn't sped up any more.
+        let velocity = config.accelerate(Vec2::new(0.0, -40.0), Vec2::Y, -1.0, TICK);
+        assert_eq!(velocity, Vec2::new(0.0, -40.0));
+    }
+
+    #[test]
+    fn thrust_is_limited_to_the_max_speed() {
+        let config = ShipConfig::default();
+
+        let velocity = thrust_for(&config, Vec2::ZERO, 1.0, 600);
+        assert!((velocity.length() - config.max_speed).abs() < 1e-4);
+
+        // Thrusting across the way the ship drifts can't push it past the max speed either.
+        let velocity = config.accelerate(Vec2::new(config.max_speed, 0.0), Vec2::Y, 1.0, 1.0);
+        assert!(velocity.length() <= config.max_speed + 1e-4);
+        assert!(velocity.x > 0.0 && velocity.y > 0.0);
+    }
+
+    #[test]
+    fn the_ship_drifts_to_a_stop_without_thrust() {
+        let config = ShipConfig::default();
+
+        // It keeps going the way it was, whichever way it faces.
+        let velocity = config.accelerate(Vec2::new(30.0, 40.0), Vec2::X, 0.0, 1.0);
+        assert!(
+            velocity.abs_diff_eq(Vec2::new(24.0, 32.0), 1e-4),
+            "{velocity}"
+        );
+
+        let velocity = config.accelerate(Vec2::new(3.0, 4.0), Vec2::X, 0.0, 1.0);
+        assert_eq!(velocity, Vec2::ZERO);
+
+        let no_drag = ShipConfig {
+            drag: 0.0,
+            ..default()
+        };
+        let velocity = Vec2::new(30.0, 40.0);
+        assert!(
+            no_drag
+                .accelerate(velocity, Vec2::X, 0.0, 1.0)
+                .abs_diff_eq(velocity, 1e-4)
+        );
+    }
+
+    #[test]
+    fn moves_along_its_velocity() {
+        let mut transform = Transform::from_xyz(1.0, 2.0, 3.0);
+
+        Velocity(Vec2::new(10.0, -20.0)).advance(&mut transform, 0.5);
+        assert_eq!(transform.translation, Vec3::new(6.0, -8.0, 3.0));
+    }
+}
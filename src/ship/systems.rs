@@ -1,189 +1,321 @@
 //! Systems for the player ship in the game.
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::prelude::*;
 
+use crate::arena::{Arena, wrap_transform};
 use crate::asteroid::Asteroid;
 
-use crate::audio::bullet::fire_bullet;
 use crate::audio::ship::*;
+use crate::audio::{AudioAssets, bullet::fire_bullet};
 use crate::bullet::{Bullet, BulletConfig};
+use crate::collision::{AsteroidGrid, CollisionPolygon, ResolvedCollisions, ShipHitAsteroid};
+use crate::controls::{ActionState, AnalogInput, ControlScheme, CursorPosition, PlayerAction};
 use crate::explosion::{ExplosionConfig, create_explosion};
-use crate::{GameState, lines_intersect, mesh_and_transform_to_points};
+use crate::interpolation::TransformInterpolation;
+use crate::physics::Velocity;
+use crate::{GameState, lines_intersect, stats::RunStats, ui::PlayerScore};
 
-use super::PlayerShip;
+use super::{
+    HealTimer, Invulnerable, LivesConfig, PLAYER_SHIP_RADIUS, PlayerLives, PlayerShip,
+    RespawnTimer, ShipActions, ShipConfig, turn_towards,
+};
+
+/// Reads the player's actions into the `ShipActions` for the next fixed ticks.
+///
+/// A frame can run any number of fixed ticks, so a press of the fire button is kept
+/// until a tick fires the bullet rather than only lasting for the frame.
+///
+/// Held actions thrust, brake and turn at full rate, and are combined with the gamepad's
+/// analog input. With mouse aim, the ship turns towards the cursor instead, at no more
+/// than its full rate.
+///
+/// # Arguments
+/// * `player_actions`: The `ActionState` resource with the player's actions this frame.
+/// * `analog`: The `AnalogInput` resource with the gamepad's sticks and triggers.
+/// * `scheme`: The `ControlScheme` resource with how the player steers.
+/// * `cursor`: The `CursorPosition` resource with where the mouse points.
+/// * `ships`: A query that retrieves the player ship's `Transform`.
+/// * `actions`: The `ShipActions` resource to write the player's actions to.
+pub fn read_ship_input(
+    player_actions: Res<ActionState>,
+    analog: Res<AnalogInput>,
+    scheme: Res<ControlScheme>,
+    cursor: Res<CursorPosition>,
+    ships: Query<&Transform, With<PlayerShip>>,
+    mut actions: ResMut<ShipActions>,
+) {
+    let held = |action| {
+        if player_actions.pressed(action) {
+            1.0
+        } else {
+            0.0
+        }
+    };
+
+    let thrust = analog.thrust + held(PlayerAction::Thrust) - held(PlayerAction::Brake);
+    let turn = match (*scheme, cursor.0, ships.single()) {
+        (ControlScheme::MouseAim, Some(target), Ok(transform)) => turn_towards(transform, target),
+        _ => analog.turn + held(PlayerAction::RotateLeft) - held(PlayerAction::RotateRight),
+    };
+    let fire = actions.fire || player_actions.just_pressed(PlayerAction::Fire);
+
+    *actions = ShipActions::from_amounts(thrust, turn, fire);
+}
 
 /// Handles player input and movement, including shooting bullets.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to spawn bullets.
-/// * `keyboard_input`: The `ButtonInput<KeyCode>` resource to check for player input
-/// * `query`: A query that retrieves the player ship's `PlayerShip` and its `Transform`.
+/// * `actions`: The `ShipActions` resource with the player's actions for this tick.
+/// * `query`: A query that retrieves the player ship's `Velocity` and its `Transform`.
 /// * `time`: The `Time` resource to calculate the movement delta.
+/// * `ship_config`: The `ShipConfig` resource with how the ship speeds up and slows down.
 /// * `bullet_config`: The `BulletConfig` resource to configure the bullets.
-/// * `asset_server`: The `AssetServer` resource to load the bullet sound asset.
-/// * `audio`: The `AudioChannel<LaserChannel>` resource to play the bullet firing
+/// * `audio_assets`: The `AudioAssets` resource to play the bullet sound.
+/// * `stats`: The `RunStats` resource to count the shots fired.
 pub fn player_input_and_movement(
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut PlayerShip, &mut Transform)>,
+    mut actions: ResMut<ShipActions>,
+    mut query: Query<(&mut Velocity, &mut Transform), With<PlayerShip>>,
     time: Res<Time>,
+    ship_config: Res<ShipConfig>,
     bullet_config: Res<BulletConfig>,
-    asset_server: Res<AssetServer>,
+    audio_assets: Res<AudioAssets>,
+    mut stats: ResMut<RunStats>,
 ) {
-    for (mut player_ship, mut transform) in query.iter_mut() {
-        if keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
-            // Lerp the speed between 0.0 and 50.0
-            player_ship.speed = player_ship.speed.lerp(50.0, time.delta_secs());
-        } else {
-            // Lerp the speed between the current speed and 0.0
-            player_ship.speed = player_ship.speed.lerp(0.0, time.delta_secs());
-        }
+    for (mut velocity, mut transform) in query.iter_mut() {
+        // Thrust the way the ship is facing, and keep drifting the way it was going
+        let facing = transform.up().truncate();
+        velocity.0 = ship_config.accelerate(
+            velocity.0,
+            facing,
+            actions.thrust_amount(),
+            time.delta_secs(),
+        );
 
-        if player_ship.speed > 0.0 {
-            // Get the direction of the ship
-            let angle = transform.rotation.to_euler(EulerRot::ZXY).0;
+        velocity.advance(&mut transform, time.delta_secs());
 
-            transform.translation.x += -angle.sin() * player_ship.speed * time.delta_secs();
-            transform.translation.y += angle.cos() * player_ship.speed * time.delta_secs();
-        }
-
-        // Rotate left
-        if keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
-            transform.rotate_local_z(6.0 * time.delta_secs());
-        }
-
-        // Rotate right
-        if keyboard_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
-            transform.rotate_local_z(-6.0 * time.delta_secs());
+        // Rotate left for a positive turn, and right for a negative one
+        if actions.turn != 0 {
+            transform.rotate_local_z(6.0 * actions.turn_amount() * time.delta_secs());
         }
 
         // Shoot
-        if keyboard_input.any_just_pressed([KeyCode::Space]) {
+        if actions.fire {
             // Spawn a bullet
             Bullet::spawn_bullet(
                 &mut commands.reborrow(),
                 *transform,
-                player_ship.speed,
+                *velocity,
                 &bullet_config,
             );
-            fire_bullet(&mut commands, &asset_server);
+            fire_bullet(
+                &mut commands,
+                transform.translation.truncate(),
+                &audio_assets,
+            );
+            stats.shots_fired += 1;
         }
     }
+
+    // The bullet has been fired, wait for the next press.
+    actions.fire = false;
 }
 
+/// Keeps the player ship inside the bounds of the arena, or wraps it around if the arena wraps.
+///
+/// # Arguments
+/// * `query`: A query that retrieves the player ship's `PlayerShip` and its `Transform`.
+/// * `arena`: The `Arena` resource to get the size of the playfield.
 pub fn check_ship_bounds(
-    mut query: Query<(&PlayerShip, &mut Transform)>,
-    window: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<(
+        &PlayerShip,
+        &mut Transform,
+        Option<&mut TransformInterpolation>,
+    )>,
+    arena: Res<Arena>,
 ) {
-    // Get the window size
-    let window = window.single().unwrap();
-    let window_size = Vec2::new(window.width(), window.height());
+    let arena_size = arena.size;
+
+    for (_, mut transform, interpolation) in query.iter_mut() {
+        if arena.wraps() {
+            wrap_transform(&arena, &mut transform, interpolation);
+            continue;
+        }
 
-    for (_, mut transform) in query.iter_mut() {
         // Check if the ship is out of bounds
-        if transform.translation.x - 5.0 < -window_size.x / 2.0 {
-            transform.translation.x = -window_size.x / 2.0 + 5.0;
-        } else if transform.translation.x + 5.0 > window_size.x / 2.0 {
-            transform.translation.x = window_size.x / 2.0 - 5.0;
-        } else if transform.translation.y - 5.0 < -window_size.y / 2.0 {
-            transform.translation.y = -window_size.y / 2.0 + 5.0;
-        } else if transform.translation.y + 5.0 > window_size.y / 2.0 {
-            transform.translation.y = window_size.y / 2.0 - 5.0;
+        if transform.translation.x - 5.0 < -arena_size.x / 2.0 {
+            transform.translation.x = -arena_size.x / 2.0 + 5.0;
+        } else if transform.translation.x + 5.0 > arena_size.x / 2.0 {
+            transform.translation.x = arena_size.x / 2.0 - 5.0;
+        } else if transform.translation.y - 5.0 < -arena_size.y / 2.0 {
+            transform.translation.y = -arena_size.y / 2.0 + 5.0;
+        } else if transform.translation.y + 5.0 > arena_size.y / 2.0 {
+            transform.translation.y = arena_size.y / 2.0 - 5.0;
         }
     }
 }
 
-/// Checks for collisions between the player ship and asteroids, and handles the destruction of both.
+/// Checks for collisions between the player ship and asteroids, and sends a `ShipHitAsteroid`
+/// event if the ship hits one.
+///
+/// The ship hits at most one asteroid per tick. The hit is resolved by `resolve_ship_hits`.
+///
+/// # Arguments
+/// * `asteroids`: A query that retrieves every `Asteroid`'s `Transform` and `CollisionPolygon`.
+/// * `ships`: A query that retrieves the player ship, its `Transform`, its `CollisionPolygon`, and whether it is `Invulnerable`.
+/// * `arena`: The `Arena` resource to find the nearest copy of each asteroid in a wrapping arena.
+/// * `grid`: The `AsteroidGrid` resource to find the asteroids near the ship.
+/// * `hits`: The `EventWriter` to send the hits to.
+pub fn check_ship_collisions(
+    asteroids: Query<(&Transform, &CollisionPolygon), With<Asteroid>>,
+    ships: Query<(Entity, &Transform, &CollisionPolygon, Has<Invulnerable>), With<PlayerShip>>,
+    arena: Res<Arena>,
+    grid: Res<AsteroidGrid>,
+    mut hits: EventWriter<ShipHitAsteroid>,
+) {
+    for (player_entity, ship_transform, ship_polygon, invulnerable) in ships.iter() {
+        // A freshly respawned ship can't be hit
+        if invulnerable {
+            continue;
+        }
+
+        // Only the asteroids near the ship can hit it
+        let nearby = grid.query(ship_transform.translation.truncate(), PLAYER_SHIP_RADIUS);
+
+        let hit = nearby.into_iter().find_map(|entry| {
+            let (asteroid_transform, asteroid_polygon) = asteroids.get(entry.entity).ok()?;
+
+            // In a wrapping arena, collide with whichever copy of the asteroid is closest
+            let offset = arena.nearest_copy_offset(
+                ship_transform.translation.truncate(),
+                asteroid_transform.translation.truncate(),
+            );
+
+            ship_polygon
+                .edges()
+                .find_map(|(s0, s1)| {
+                    asteroid_polygon
+                        .edges()
+                        .find_map(|(a0, a1)| lines_intersect(s0, s1, a0 + offset, a1 + offset))
+                })
+                .map(|point| ShipHitAsteroid {
+                    ship: player_entity,
+                    asteroid: entry.entity,
+                    point,
+                })
+        });
+
+        if let Some(hit) = hit {
+            hits.write(hit);
+        }
+    }
+}
+
+/// Damages the ship and destroys the asteroid of this tick's `ShipHitAsteroid` events.
+///
+/// Ships and asteroids that were already consumed by another collision this tick are
+/// skipped, so an asteroid that was just shot can't hit the ship as well. A destroyed ship
+/// costs the player a life, and the game is over once they have none left.
 ///
 /// # Arguments
 /// * `commands`: The `Commands` resource to despawn the player ship and asteroids
-/// * `asteroids`: A query that retrieves every `Asteroid` and its `Transform`.
-/// * `ships`: A query that retrieves the player ship's `PlayerShip`, its `Transform`, and its `Mesh2d`.
+/// * `hits`: The `EventReader` to read the hits from.
+/// * `asteroids`: A query that retrieves every `Asteroid`.
+/// * `ships`: A query that retrieves the player ship's `PlayerShip`.
+/// * `resolved`: The `ResolvedCollisions` resource to consume each entity at most once.
 /// * `explosion_config`: The `ExplosionConfig` resource to create explosions.
-/// * `time`: The `Time` resource to determine the frequency of asteroid spawning.
-/// * `meshes`: The `Assets<Mesh>` resource to get the mesh of the asteroids and ships.
-/// * `asset_server`: The `AssetServer` resource to play sound effects.
-/// * `next_state`: The `NextState<GameState>` resource to change the game state to `GameOver` if the player ship is destroyed.
-pub fn check_ship_collisions(
+/// * `time`: The `Time` resource to start the explosions.
+/// * `audio_assets`: The `AudioAssets` resource to play sound effects.
+/// * `lives`: The `PlayerLives` resource to take a life from when the ship is destroyed.
+/// * `respawn_timer`: The `RespawnTimer` resource to start when the ship is destroyed.
+/// * `stats`: The `RunStats` resource to count the destroyed asteroids.
+/// * `next_state`: The `NextState<GameState>` resource to change the game state to `GameOver` if the player is out of lives.
+pub fn resolve_ship_hits(
     mut commands: Commands,
-    asteroids: Query<(Entity, &Asteroid, &Transform, &Mesh2d)>,
-    mut ships: Query<(Entity, &mut PlayerShip, &Transform, &Mesh2d)>,
+    mut hits: EventReader<ShipHitAsteroid>,
+    asteroids: Query<&Asteroid>,
+    mut ships: Query<&mut PlayerShip>,
+    mut resolved: ResMut<ResolvedCollisions>,
     explosion_config: Res<ExplosionConfig>,
     time: Res<Time>,
-    meshes: Res<Assets<Mesh>>,
-    asset_server: Res<AssetServer>,
+    audio_assets: Res<AudioAssets>,
+    mut lives: ResMut<PlayerLives>,
+    mut respawn_timer: ResMut<RespawnTimer>,
+    mut stats: ResMut<RunStats>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (player_entity, mut player_ship, ship_transform, ship_mesh) in ships.iter_mut() {
-        for (asteroid_entity, asteroid, asteroid_transform, asteroid_mesh) in asteroids.iter() {
-            let asteroid_diameter = asteroid.size.diameter();
-
-            // Check if the ship is colliding with the asteroid
-            if ship_transform
-                .translation
-                .distance(asteroid_transform.translation)
-                < 5.0 + asteroid_diameter
-            {
-                // Get the asteroid's points
-                let asteroid_mesh = meshes.get(&asteroid_mesh.0).unwrap();
-                let asteroid_points =
-                    mesh_and_transform_to_points(asteroid_mesh, asteroid_transform);
-
-                // Get the ship's points
-                let ship_mesh = meshes.get(&ship_mesh.0).unwrap();
-                let ship_points = mesh_and_transform_to_points(ship_mesh, ship_transform);
-
-                for s in ship_points.windows(2) {
-                    for a in asteroid_points.windows(2) {
-                        if let Some(p) = lines_intersect(s[0], s[1], a[0], a[1]) {
-                            // Get the point of contact
-                            let point_of_contact = Transform::from_translation(p.extend(-1.0));
-
-                            // Damage the ship
-                            player_ship.health -= asteroid.size as i32;
-
-                            if player_ship.health <= 0 {
-                                // Create the transform for the explosion
-                                let point_of_contact = Transform::from_translation(p.extend(-1.0));
-
-                                // Blow up the ship
-                                commands.entity(player_entity).despawn();
-
-                                // Create an explosion
-                                create_explosion(
-                                    &mut commands,
-                                    point_of_contact,
-                                    &explosion_config,
-                                    &time,
-                                    false,
-                                );
-                                ship_destroyed(&mut commands, &asset_server);
-                                next_state.set(GameState::GameOver);
-                            } else {
-                                ship_hit(&mut commands, &asset_server);
-                            }
-
-                            // Blow up the asteroid
-                            commands.entity(asteroid_entity).despawn();
-
-                            // Create an explosion
-                            create_explosion(
-                                &mut commands,
-                                point_of_contact,
-                                &explosion_config,
-                                &time,
-                                true,
-                            );
-                            return;
-                        }
-                    }
-                }
+    for hit in hits.read() {
+        let (Ok(asteroid), Ok(mut player_ship)) =
+            (asteroids.get(hit.asteroid), ships.get_mut(hit.ship))
+        else {
+            continue;
+        };
+
+        if !resolved.consume(hit.ship, hit.asteroid) {
+            continue;
+        }
+
+        // Get the point of contact
+        let point_of_contact = Transform::from_translation(hit.point.extend(-1.0));
+
+        // Damage the ship
+        player_ship.health -= asteroid.size as i32;
+
+        if player_ship.health <= 0 {
+            // Blow up the ship
+            commands.entity(hit.ship).despawn();
+
+            // Create an explosion
+            create_explosion(
+                &mut commands,
+                point_of_contact,
+                &explosion_config,
+                &time,
+                false,
+            );
+            ship_destroyed(&mut commands, hit.point, &audio_assets);
+
+            lives.remaining = lives.remaining.saturating_sub(1);
+            if lives.remaining == 0 {
+                next_state.set(GameState::GameOver);
+            } else {
+                respawn_timer.0.reset();
             }
+        } else {
+            ship_hit(&mut commands, hit.point, &audio_assets);
         }
+
+        // Blow up the asteroid
+        commands.entity(hit.asteroid).despawn();
+        stats.record_destroyed(asteroid.size);
+
+        // Create an explosion
+        create_explosion(
+            &mut commands,
+            point_of_contact,
+            &explosion_config,
+            &time,
+            true,
+        );
     }
 }
 
-pub fn heal_player(mut query: Query<&mut PlayerShip>) {
+/// Slowly heals the player ship back up to full health.
+///
+/// # Arguments
+/// * `query`: A query that retrieves the player ship's `PlayerShip`.
+/// * `heal_timer`: The `HealTimer` resource that decides when the ship heals.
+/// * `time`: The `Time` resource to advance the heal timer.
+pub fn heal_player(
+    mut query: Query<&mut PlayerShip>,
+    mut heal_timer: ResMut<HealTimer>,
+    time: Res<Time>,
+) {
+    if !heal_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
     for mut player_ship in query.iter_mut() {
         player_ship.health = (player_ship.health + 1).min(100);
     }
@@ -206,3 +338,333 @@ pub fn color_player(
         ));
     }
 }
+
+/// Gives the player a fresh set of lives at the start of a run.
+///
+/// # Arguments
+/// * `lives`: The `PlayerLives` resource to reset.
+/// * `respawn_timer`: The `RespawnTimer` resource to reset.
+/// * `config`: The `LivesConfig` resource with the number of lives to start with.
+pub fn restart_lives(
+    mut lives: ResMut<PlayerLives>,
+    mut respawn_timer: ResMut<RespawnTimer>,
+    config: Res<LivesConfig>,
+) {
+    *lives = PlayerLives {
+        remaining: config.starting_lives.max(1),
+        next_extra_life: config.extra_life_every,
+    };
+    respawn_timer.0 = Timer::from_seconds(config.respawn_delay, TimerMode::Once);
+}
+
+/// Respawns the player ship in the centre of the arena after it was destroyed, once the
+/// respawn delay is over and no asteroid is near the centre.
+///
+/// The new ship is invulnerable for a few seconds.
+///
+/// # Arguments
+/// * `commands`: The `Commands` resource to spawn the player ship.
+/// * `meshes`: The `Assets<Mesh>` resource to create the player ship mesh.
+/// * `materials`: The `Assets<ColorMaterial>` resource to create the player ship material.
+/// * `ships`: A query that finds the player ship, if there is one.
+/// * `lives`: The `PlayerLives` resource to check whether the player has a ship left.
+/// * `respawn_timer`: The `RespawnTimer` resource that delays the respawn.
+/// * `grid`: The `AsteroidGrid` resource to check that the centre is clear.
+/// * `config`: The `LivesConfig` resource with the respawn clearance and invulnerability.
+/// * `time`: The `Time` resource to advance the respawn timer.
+pub fn respawn_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    ships: Query<(), With<PlayerShip>>,
+    lives: Res<PlayerLives>,
+    mut respawn_timer: ResMut<RespawnTimer>,
+    grid: Res<AsteroidGrid>,
+    config: Res<LivesConfig>,
+    time: Res<Time>,
+) {
+    if !ships.is_empty() || lives.remaining == 0 {
+        return;
+    }
+
+    if !respawn_timer.0.tick(time.delta()).finished() {
+        return;
+    }
+
+    // Wait until the ship won't respawn on top of an asteroid.
+    if grid
+        .query(Vec2::ZERO, config.respawn_clearance)
+        .next()
+        .is_some()
+    {
+        return;
+    }
+
+    let ship = PlayerShip::spawn_new(&mut commands, &mut meshes, &mut materials);
+    commands
+        .entity(ship)
+        .insert(Invulnerable(Timer::from_seconds(
+            config.invulnerability,
+            TimerMode::Once,
+        )));
+}
+
+/// Counts down the invulnerability of the player ship, and makes it vulnerable again
+/// once it runs out.
+///
+/// # Arguments
+/// * `commands`: The `Commands` resource to remove the invulnerability.
+/// * `query`: A query that retrieves the invulnerable player ship and its `Visibility`.
+/// * `time`: The `Time` resource to advance the invulnerability timer.
+pub fn update_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable, mut visibility) in query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+/// Flashes the player ship while it is invulnerable.
+///
+/// # Arguments
+/// * `query`: A query that retrieves the invulnerable player ship and its `Visibility`.
+pub fn flash_invulnerable_player(mut query: Query<(&Invulnerable, &mut Visibility)>) {
+    for (invulnerable, mut visibility) in query.iter_mut() {
+        // Blink five times a second.
+        let shown = ((invulnerable.0.elapsed_secs() * 10.0) as u32).is_multiple_of(2);
+
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Awards an extra life every time the player's score passes another threshold.
+///
+/// # Arguments
+/// * `score`: The `PlayerScore` resource, after this tick's `ScoreEvent`s were tallied.
+/// * `lives`: The `PlayerLives` resource to add lives to.
+/// * `config`: The `LivesConfig` resource with the score needed for each extra life.
+pub fn award_extra_lives(
+    score: Res<PlayerScore>,
+    mut lives: ResMut<PlayerLives>,
+    config: Res<LivesConfig>,
+) {
+    if config.extra_life_every <= 0 {
+        return;
+    }
+
+    while score.0 >= lives.next_extra_life {
+        lives.remaining += 1;
+        lives.next_extra_life += config.extra_life_every;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{asteroid::AsteroidSize, ship::PLAYER_SHIP_POINTS};
+
+    /// Creates a world in which the player has just lost a ship, with two lives left.
+    fn respawn_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.init_resource::<Time>();
+        world.insert_resource(LivesConfig::default());
+        world.insert_resource(PlayerLives {
+            remaining: 2,
+            next_extra_life: 100,
+        });
+        world.insert_resource(RespawnTimer(Timer::from_seconds(1.5, TimerMode::Once)));
+
+        let mut grid = AsteroidGrid::default();
+        grid.clear(&Arena::default());
+        world.insert_resource(grid);
+
+        world
+    }
+
+    /// Advances time by `seconds` and runs `respawn_player` once.
+    fn run_respawn(world: &mut World, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(respawn_player).unwrap();
+    }
+
+    fn ship_count(world: &mut World) -> usize {
+        world
+            .query_filtered::<(), With<PlayerShip>>()
+            .iter(world)
+            .count()
+    }
+
+    #[test]
+    fn respawns_an_invulnerable_ship_after_the_delay() {
+        let mut world = respawn_world();
+
+        run_respawn(&mut world, 1.0);
+        assert_eq!(ship_count(&mut world), 0);
+
+        run_respawn(&mut world, 1.0);
+        let invulnerable = world
+            .query_filtered::<&Invulnerable, With<PlayerShip>>()
+            .iter(&world)
+            .count();
+        assert_eq!(invulnerable, 1);
+
+        // There is already a ship, so no other one spawns.
+        run_respawn(&mut world, 1.0);
+        assert_eq!(ship_count(&mut world), 1);
+    }
+
+    #[test]
+    fn waits_for_the_centre_to_clear_before_respawning() {
+        let mut world = respawn_world();
+        let asteroid = world.spawn_empty().id();
+        world
+            .resource_mut::<AsteroidGrid>()
+            .insert(asteroid, Vec2::new(50.0, 0.0), 10.0);
+
+        run_respawn(&mut world, 2.0);
+        assert_eq!(ship_count(&mut world), 0);
+
+        world
+            .resource_mut::<AsteroidGrid>()
+            .clear(&Arena::default());
+        run_respawn(&mut world, 0.0);
+        assert_eq!(ship_count(&mut world), 1);
+    }
+
+    #[test]
+    fn an_asteroid_touching_a_rear_corner_hits_the_ship() {
+        let mut world = World::new();
+        world.insert_resource(Arena::default());
+        world.init_resource::<Events<ShipHitAsteroid>>();
+
+        let mut polygon = CollisionPolygon::new(PLAYER_SHIP_POINTS);
+        polygon.update(&Transform::default());
+        world.spawn((PlayerShip { health: 100 }, Transform::default(), polygon));
+
+        // A spike pointing at the ship's bottom left corner, whose tip just reaches past
+        // it. Its centre is further away than the ship's half width plus its own radius.
+        let size = AsteroidSize::Small;
+        let towards_ship = Vec2::ONE.normalize();
+        let distance = 6.5 + size.diameter();
+        let tip = distance - PLAYER_SHIP_RADIUS + 1.0;
+        let side = towards_ship.perp() * 3.0;
+        let outline = [
+            towards_ship * tip,
+            side,
+            -towards_ship * 3.0,
+            -side,
+            towards_ship * tip,
+        ]
+        .map(|point| point.extend(0.0));
+
+        let transform = Transform::from_translation((-towards_ship * distance).extend(0.0));
+        let mut polygon = CollisionPolygon::new(&outline);
+        polygon.update(&transform);
+        let asteroid = world.spawn((Asteroid { size }, transform, polygon)).id();
+
+        let mut grid = AsteroidGrid::default();
+        grid.clear(&Arena::default());
+        grid.insert(asteroid, transform.translation.truncate(), size.diameter());
+        world.insert_resource(grid);
+
+        world.run_system_once(check_ship_collisions).unwrap();
+
+        let hits = world.resource::<Events<ShipHitAsteroid>>();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn does_not_respawn_without_lives() {
+        let mut world = respawn_world();
+        world.resource_mut::<PlayerLives>().remaining = 0;
+
+        run_respawn(&mut world, 2.0);
+        assert_eq!(ship_count(&mut world), 0);
+    }
+
+    #[test]
+    fn awards_a_life_for_every_threshold_passed() {
+        let mut world = World::new();
+        world.insert_resource(LivesConfig::default());
+        world.insert_resource(PlayerLives {
+            remaining: 1,
+            next_extra_life: 100,
+        });
+
+        world.insert_resource(PlayerScore(99));
+        world.run_system_once(award_extra_lives).unwrap();
+        assert_eq!(world.resource::<PlayerLives>().remaining, 1);
+
+        world.insert_resource(PlayerScore(100));
+        world.run_system_once(award_extra_lives).unwrap();
+        assert_eq!(world.resource::<PlayerLives>().remaining, 2);
+        assert_eq!(world.resource::<PlayerLives>().next_extra_life, 200);
+
+        // A big jump in score passes several thresholds at once.
+        world.insert_resource(PlayerScore(450));
+        world.run_system_once(award_extra_lives).unwrap();
+        assert_eq!(world.resource::<PlayerLives>().remaining, 5);
+        assert_eq!(world.resource::<PlayerLives>().next_extra_life, 500);
+    }
+
+    #[test]
+    fn awards_no_lives_when_extra_lives_are_off() {
+        let mut world = World::new();
+        world.insert_resource(LivesConfig {
+            extra_life_every: 0,
+            ..default()
+        });
+        world.insert_resource(PlayerLives {
+            remaining: 1,
+            next_extra_life: 0,
+        });
+        world.insert_resource(PlayerScore(1000));
+
+        world.run_system_once(award_extra_lives).unwrap();
+        assert_eq!(world.resource::<PlayerLives>().remaining, 1);
+    }
+
+    #[test]
+    fn restarts_lives_from_the_config() {
+        let mut world = World::new();
+        world.insert_resource(LivesConfig {
+            starting_lives: 0,
+            extra_life_every: 250,
+            respawn_delay: 2.0,
+            ..default()
+        });
+        world.insert_resource(PlayerLives {
+            remaining: 7,
+            next_extra_life: 1000,
+        });
+        world.init_resource::<RespawnTimer>();
+
+        world.run_system_once(restart_lives).unwrap();
+
+        // A run always starts with at least one ship.
+        let lives = world.resource::<PlayerLives>();
+        assert_eq!(lives.remaining, 1);
+        assert_eq!(lives.next_extra_life, 250);
+
+        let timer = &world.resource::<RespawnTimer>().0;
+        assert_eq!(timer.duration(), Duration::from_secs(2));
+        assert_eq!(timer.mode(), TimerMode::Once);
+    }
+}
@@ -0,0 +1,45 @@
+//! Data for the statistics of a run.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::asteroid::AsteroidSize;
+
+/// What the player did over the current or last run.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct RunStats {
+    /// How long the run lasted. Only set once the run is over.
+    pub time_survived: Duration,
+    /// How many bullets the player fired.
+    pub shots_fired: u32,
+    /// How many of the player's bullets hit an asteroid.
+    pub shots_hit: u32,
+    /// How many small, medium and large asteroids the player destroyed, by shooting or ramming them.
+    asteroids_destroyed: [u32; 3],
+}
+
+impl RunStats {
+    /// Counts a destroyed asteroid of the given size.
+    pub fn record_destroyed(&mut self, size: AsteroidSize) {
+        self.asteroids_destroyed[Self::size_index(size)] += 1;
+    }
+
+    /// Returns how many asteroids of the given size the player destroyed.
+    pub fn destroyed(&self, size: AsteroidSize) -> u32 {
+        self.asteroids_destroyed[Self::size_index(size)]
+    }
+
+    /// Returns the share of fired bullets that hit an asteroid, or `None` if none were fired.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots_fired > 0).then(|| self.shots_hit as f32 / self.shots_fired as f32)
+    }
+
+    fn size_index(size: AsteroidSize) -> usize {
+        match size {
+            AsteroidSize::Small => 0,
+            AsteroidSize::Medium => 1,
+            AsteroidSize::Large => 2,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! # Stats
+//!
+//! This module keeps track of how the player did over a run, for the game over screen.
+
+pub mod data;
+pub use data::*;
+pub mod systems;
+pub use systems::*;
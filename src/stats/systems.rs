@@ -0,0 +1,24 @@
+//! Systems for the statistics of a run.
+
+use bevy::prelude::*;
+
+use crate::RunClock;
+
+use super::RunStats;
+
+/// Clears the statistics of the last run.
+///
+/// # Arguments
+/// * `stats`: The `RunStats` resource to clear.
+pub fn restart_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+/// Records how long the run lasted once it is over.
+///
+/// # Arguments
+/// * `stats`: The `RunStats` resource to record the time in.
+/// * `run_clock`: The `RunClock` resource that timed the run.
+pub fn finish_stats(mut stats: ResMut<RunStats>, run_clock: Res<RunClock>) {
+    stats.time_survived = run_clock.0.elapsed();
+}
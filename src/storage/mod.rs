@@ -0,0 +1,88 @@
+//! # Storage
+//!
+//! This module finds where the game keeps the files it saves between sessions, and reads
+//! and writes its settings files.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use bevy::platform::collections::HashMap;
+
+/// The name of the game's folder in the user's data directory.
+const APP_DIR: &str = "asteroids";
+
+/// Returns the path of a file in the game's folder of the user's data directory.
+///
+/// This is `$XDG_DATA_HOME/asteroids` or `~/.local/share/asteroids` on Linux,
+/// `~/Library/Application Support/asteroids` on macOS, and `%APPDATA%\asteroids` on Windows.
+/// Returns `None` if there is no data directory, like on the web, in which case nothing
+/// should be saved.
+///
+/// # Arguments
+/// * `name`: The name of the file.
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(APP_DIR).join(name))
+}
+
+/// Returns the user's data directory, if the platform has one.
+fn data_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    if cfg!(target_arch = "wasm32") {
+        None
+    } else if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+    }
+}
+
+/// Reads a settings file of `key value` lines.
+///
+/// Blank lines and lines starting with `#` are skipped, so settings files can be edited by hand.
+///
+/// # Arguments
+/// * `path`: The settings file to read.
+pub fn load_settings(path: &Path) -> io::Result<HashMap<String, String>> {
+    let mut settings = HashMap::default();
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        settings.insert(key.to_string(), value.trim().to_string());
+    }
+
+    Ok(settings)
+}
+
+/// Writes a settings file of `key value` lines, creating its folder if needed.
+///
+/// # Arguments
+/// * `path`: The settings file to write.
+/// * `settings`: The settings to write, in order.
+pub fn save_settings(path: &Path, settings: &[(&str, String)]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    for (key, value) in settings {
+        writeln!(writer, "{key} {value}")?;
+    }
+    writer.flush()
+}
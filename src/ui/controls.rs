@@ -0,0 +1,191 @@
+//! The controls menu, opened from the settings menu, to rebind the player's actions.
+
+use bevy::prelude::*;
+
+use crate::{
+    controls::{ActionState, BINDABLE_KEYS, InputMap, PlayerAction, Rebinding},
+    ui::{Menu, MenuActivated, SettingsState, menu_item, menu_node},
+};
+
+/// Marker component for the controls menu UI.
+#[derive(Component)]
+pub struct ControlsMenu;
+
+/// The options on the controls menu.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlsItem {
+    /// Rebind an action.
+    Action(PlayerAction),
+    /// Put every action back on its default keys.
+    ResetDefaults,
+    /// Go back to the settings menu.
+    Back,
+}
+
+impl ControlsItem {
+    /// Returns the label of the option.
+    ///
+    /// # Arguments
+    /// * `input_map`: The keys bound to every action.
+    /// * `rebinding`: The action waiting for a new key, if any.
+    pub fn label(self, input_map: &InputMap, rebinding: Option<PlayerAction>) -> String {
+        match self {
+            ControlsItem::Action(action) if rebinding == Some(action) => {
+                format!("{}: press a key...", action.label())
+            }
+            ControlsItem::Action(action) => {
+                format!("{}: {}", action.label(), input_map.keys_label(action))
+            }
+            ControlsItem::ResetDefaults => "Reset to Defaults".to_string(),
+            ControlsItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+/// Sets up the controls menu.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+/// * `input_map`: The `InputMap` resource to show.
+pub fn setup_controls_menu(mut commands: Commands, input_map: Res<InputMap>) {
+    commands
+        .spawn((
+            ControlsMenu,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Controls"),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn((Menu::default(), menu_node()))
+                .with_children(|menu| {
+                    let items = PlayerAction::REBINDABLE
+                        .into_iter()
+                        .map(ControlsItem::Action)
+                        .chain([ControlsItem::ResetDefaults, ControlsItem::Back]);
+
+                    for item in items {
+                        menu.spawn((item, menu_item(item.label(&input_map, None))));
+                    }
+                });
+        });
+}
+
+/// Handles the options picked on the controls menu.
+///
+/// Picking an action waits for the next key press to bind it to, see `capture_rebinding`.
+///
+/// # Arguments
+/// * `commands`: The commands to start rebinding an action.
+/// * `actions`: The `ActionState` resource to check for going back.
+/// * `rebinding`: The `Rebinding` resource, while an action is waiting for a key.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `items`: A query that retrieves the `ControlsItem` of every option.
+/// * `input_map`: The `InputMap` resource to reset.
+/// * `next_state`: The `NextState<SettingsState>` resource to go back to the settings menu.
+pub fn handle_controls_menu_input(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    rebinding: Option<Res<Rebinding>>,
+    mut activated: EventReader<MenuActivated>,
+    items: Query<&ControlsItem>,
+    mut input_map: ResMut<InputMap>,
+    mut next_state: ResMut<NextState<SettingsState>>,
+) {
+    if rebinding.is_some() {
+        activated.clear();
+        return;
+    }
+
+    for MenuActivated(entity) in activated.read() {
+        match items.get(*entity) {
+            Ok(ControlsItem::Action(action)) => commands.insert_resource(Rebinding(*action)),
+            Ok(ControlsItem::ResetDefaults) => *input_map = InputMap::default(),
+            Ok(ControlsItem::Back) => next_state.set(SettingsState::Open),
+            Err(_) => {}
+        }
+    }
+
+    if actions.just_pressed(PlayerAction::MenuBack) {
+        next_state.set(SettingsState::Open);
+    }
+}
+
+/// Binds the action being rebound to the next key the player presses. `Escape`, or the
+/// back button of a gamepad, cancels.
+///
+/// # Arguments
+/// * `commands`: The commands to finish rebinding.
+/// * `actions`: The `ActionState` resource to check for cancelling.
+/// * `keyboard_input`: The `ButtonInput<KeyCode>` resource to read the new key from.
+/// * `rebinding`: The `Rebinding` resource with the action waiting for a key.
+/// * `input_map`: The `InputMap` resource to bind the key in.
+pub fn capture_rebinding(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    rebinding: Res<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+) {
+    if actions.just_pressed(PlayerAction::MenuBack) {
+        commands.remove_resource::<Rebinding>();
+        return;
+    }
+
+    let key = keyboard_input
+        .get_just_pressed()
+        .find(|key| BINDABLE_KEYS.contains(key));
+
+    if let Some(key) = key {
+        input_map.bind(rebinding.0, *key);
+        commands.remove_resource::<Rebinding>();
+    }
+}
+
+/// Updates the labels of the controls menu.
+///
+/// # Arguments
+/// * `input_map`: The `InputMap` resource to show.
+/// * `rebinding`: The `Rebinding` resource, while an action is waiting for a key.
+/// * `items`: A query that retrieves the `ControlsItem` and `Text` of every option.
+pub fn update_controls_menu(
+    input_map: Res<InputMap>,
+    rebinding: Option<Res<Rebinding>>,
+    mut items: Query<(&ControlsItem, &mut Text)>,
+) {
+    let rebinding = rebinding.map(|rebinding| rebinding.0);
+
+    for (item, mut text) in items.iter_mut() {
+        let label = item.label(&input_map, rebinding);
+
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+/// Deletes the controls menu UI from the game, and stops any rebinding.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the controls menu UI.
+/// * `query`: A query that retrieves all entities with the `ControlsMenu` component.
+pub fn despawn_controls_menu(mut commands: Commands, query: Query<Entity, With<ControlsMenu>>) {
+    commands.remove_resource::<Rebinding>();
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
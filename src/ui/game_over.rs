@@ -1,34 +1,374 @@
-use bevy::prelude::*;
+//! The game over screen, with the statistics of the run and what to do next.
 
-use crate::{GameState, ui::PlayerScore};
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+
+use crate::{
+    GameState,
+    asteroid::AsteroidSize,
+    controls::{ActionState, PlayerAction},
+    highscore::{
+        DEFAULT_NAME, HighScoreSettings, HighScores, MAX_NAME_LENGTH, PendingHighScore,
+        save_high_scores,
+    },
+    stats::RunStats,
+    ui::{Menu, MenuActivated, PlayerScore, menu_item, menu_node},
+};
+
+/// Marker component for the game over UI.
+#[derive(Component)]
+pub struct GameOverUi;
+
+/// Marker component for the part of the game over UI that asks for a high score name.
+#[derive(Component)]
+pub struct NameEntry;
+
+/// Marker component for the text that shows the name being entered.
+#[derive(Component)]
+pub struct NameEntryText;
+
+/// Marker component for the option that saves the high score name, for touch screens.
+#[derive(Component)]
+pub struct SaveNameButton;
+
+/// The options on the game over screen.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverAction {
+    /// Start a new run.
+    Restart,
+    /// Show the high score table.
+    HighScores,
+    /// Go back to the main menu.
+    MainMenu,
+}
+
+/// Sets up the game over screen with the player's final score and the statistics of the run.
+///
+/// If the run made the high score table, the player is asked for a name before the menu shows up.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+/// * `score`: The `PlayerScore` resource with the final score.
+/// * `stats`: The `RunStats` resource with the statistics of the run.
+/// * `pending`: The `PendingHighScore` resource, if the run made the high score table.
+pub fn setup_game_over_ui(
+    mut commands: Commands,
+    score: Res<PlayerScore>,
+    stats: Res<RunStats>,
+    pending: Option<Res<PendingHighScore>>,
+) {
+    let survived = stats.time_survived.as_secs();
+    let accuracy = match stats.accuracy() {
+        Some(accuracy) => format!("{:.0}%", accuracy * 100.0),
+        None => "-".to_string(),
+    };
+
+    let summary = format!(
+        "Time Survived: {}:{:02}\nShots Fired: {}\nAccuracy: {}\n\
+         Asteroids Destroyed: {} Large, {} Medium, {} Small",
+        survived / 60,
+        survived % 60,
+        stats.shots_fired,
+        accuracy,
+        stats.destroyed(AsteroidSize::Large),
+        stats.destroyed(AsteroidSize::Medium),
+        stats.destroyed(AsteroidSize::Small),
+    );
 
-pub fn setup_game_over_ui(mut commands: Commands, score: Res<PlayerScore>) {
     commands
-        .spawn((Node {
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            flex_direction: FlexDirection::Column,
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            ..default()
-        },))
-        .with_child((
-            TextLayout::new_with_justify(JustifyText::Center),
-            TextFont {
-                font_size: 40.0,
-                line_height: bevy::text::LineHeight::RelativeToFont(2.0),
+        .spawn((
+            GameOverUi,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
                 ..default()
             },
-            Text::new(format!("Game Over\nFinal Score: {}", score.0)),
-        ));
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextFont {
+                    font_size: 40.0,
+                    line_height: bevy::text::LineHeight::RelativeToFont(2.0),
+                    ..default()
+                },
+                Text::new(format!("Game Over\nFinal Score: {}", score.0)),
+            ));
+            parent.spawn((
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextFont {
+                    font_size: 24.0,
+                    line_height: bevy::text::LineHeight::RelativeToFont(1.5),
+                    ..default()
+                },
+                Text::new(summary),
+            ));
+
+            if pending.is_some() {
+                parent
+                    .spawn((
+                        NameEntry,
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::top(Val::Px(20.0)),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|entry| {
+                        entry.spawn((
+                            NameEntryText,
+                            TextLayout::new_with_justify(JustifyText::Center),
+                            TextFont {
+                                font_size: 30.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(1.0, 0.85, 0.2)),
+                            Text::new(name_entry_text("")),
+                        ));
+                        // Without a keyboard, a touch screen can only save the default name.
+                        entry
+                            .spawn((Menu::default(), menu_node()))
+                            .with_child((SaveNameButton, menu_item("Save")));
+                    });
+            } else {
+                spawn_game_over_menu(parent);
+            }
+        });
+}
+
+/// Spawns the menu of the game over screen.
+///
+/// # Arguments
+/// * `parent`: The node to spawn the menu in.
+fn spawn_game_over_menu(parent: &mut ChildSpawnerCommands) {
+    parent
+        .spawn((Menu::default(), menu_node()))
+        .with_children(|menu| {
+            menu.spawn((GameOverAction::Restart, menu_item("Restart")));
+            menu.spawn((GameOverAction::HighScores, menu_item("High Scores")));
+            menu.spawn((GameOverAction::MainMenu, menu_item("Main Menu")));
+        });
+}
+
+/// Returns the prompt for a high score name, with the name entered so far.
+fn name_entry_text(name: &str) -> String {
+    format!("New High Score!\nEnter your name: {name}_")
+}
+
+/// Lets the player type a name for their high score, and adds it to the table on `Enter`.
+///
+/// Letters and digits are typed in upper case and `Backspace` deletes the last one. As a
+/// gamepad or a touch screen can't type, the confirm action and the `Save` option also
+/// save the name typed so far, and the back action skips the name. Either way an empty
+/// name is saved as `DEFAULT_NAME`. Once the score is saved, the name entry is replaced
+/// by the rank the run made and the menu.
+///
+/// # Arguments
+/// * `commands`: The commands to swap the name entry for the menu.
+/// * `keyboard_input`: The `EventReader` for the keys the player types.
+/// * `player_actions`: The `ActionState` resource to check for the confirm and back actions.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `save_buttons`: A query that checks that a picked option is the `SaveNameButton`.
+/// * `pending`: The `PendingHighScore` resource with the name typed so far.
+/// * `scores`: The `HighScores` resource to add the entry to.
+/// * `settings`: The `HighScoreSettings` resource to get the path to save to.
+/// * `name_text`: A query that retrieves the text showing the name.
+/// * `name_entry`: A query that retrieves the name entry UI.
+/// * `root`: A query that retrieves the root of the game over UI.
+pub fn enter_high_score_name(
+    mut commands: Commands,
+    mut keyboard_input: EventReader<KeyboardInput>,
+    player_actions: Res<ActionState>,
+    mut activated: EventReader<MenuActivated>,
+    save_buttons: Query<(), With<SaveNameButton>>,
+    mut pending: ResMut<PendingHighScore>,
+    mut scores: ResMut<HighScores>,
+    settings: Res<HighScoreSettings>,
+    mut name_text: Query<&mut Text, With<NameEntryText>>,
+    name_entry: Query<Entity, With<NameEntry>>,
+    root: Query<Entity, With<GameOverUi>>,
+) {
+    let skip = player_actions.just_pressed(PlayerAction::MenuBack);
+    let mut submit = skip
+        || player_actions.just_pressed(PlayerAction::MenuConfirm)
+        || activated
+            .read()
+            .any(|MenuActivated(entity)| save_buttons.contains(*entity));
+
+    for event in keyboard_input.read() {
+        if !event.state.is_pressed() || submit {
+            continue;
+        }
+
+        match &event.logical_key {
+            // Keys held down since the run ended only repeat, so they don't type anything.
+            Key::Character(text) if !event.repeat => {
+                for character in text.chars().filter(char::is_ascii_alphanumeric) {
+                    if pending.0.name.len() < MAX_NAME_LENGTH {
+                        pending.0.name.push(character.to_ascii_uppercase());
+                    }
+                }
+            }
+            Key::Backspace => {
+                pending.0.name.pop();
+            }
+            Key::Enter if !event.repeat => submit = true,
+            _ => {}
+        }
+    }
+
+    if !submit {
+        for mut text in name_text.iter_mut() {
+            text.0 = name_entry_text(&pending.0.name);
+        }
+        return;
+    }
+
+    let mut entry = pending.0.clone();
+    if skip || entry.name.is_empty() {
+        entry.name = DEFAULT_NAME.to_string();
+    }
+
+    let rank = scores.insert(entry);
+    save_high_scores(&scores, &settings);
+    commands.remove_resource::<PendingHighScore>();
+
+    for entity in name_entry.iter() {
+        commands.entity(entity).despawn();
+    }
+    for entity in root.iter() {
+        commands.entity(entity).with_children(|parent| {
+            if let Some(rank) = rank {
+                parent.spawn((
+                    TextFont {
+                        font_size: 30.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(1.0, 0.85, 0.2)),
+                    Text::new(format!("#{} on the high scores!", rank + 1)),
+                    Node {
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..default()
+                    },
+                ));
+            }
+            spawn_game_over_menu(parent);
+        });
+    }
 }
 
+/// Handles the options picked on the game over screen. The restart action restarts right
+/// away, unless the player is typing a high score name.
+///
+/// # Arguments
+/// * `player_actions`: The `ActionState` resource to check for the restart action.
+/// * `pending`: The `PendingHighScore` resource, while the player is typing a name.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `actions`: A query that retrieves the `GameOverAction` of every option.
+/// * `next_state`: The `NextState<GameState>` resource to leave the game over screen.
 pub fn handle_game_over_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_actions: Res<ActionState>,
+    pending: Option<Res<PendingHighScore>>,
+    mut activated: EventReader<MenuActivated>,
+    actions: Query<&GameOverAction>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyR) {
-        // Restart the game when 'R' is pressed
+    if pending.is_none() && player_actions.just_pressed(PlayerAction::Restart) {
+        // Restart the game when restart is pressed
         next_state.set(GameState::Game);
     }
+
+    for MenuActivated(entity) in activated.read() {
+        match actions.get(*entity) {
+            Ok(GameOverAction::Restart) => next_state.set(GameState::Game),
+            Ok(GameOverAction::HighScores) => next_state.set(GameState::HighScores),
+            Ok(GameOverAction::MainMenu) => next_state.set(GameState::MainMenu),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Deletes the game over UI from the game.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the game over UI.
+/// * `query`: A query that retrieves all entities with the `GameOverUi` component.
+pub fn despawn_game_over_ui(mut commands: Commands, query: Query<Entity, With<GameOverUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::highscore::HighScore;
+
+    /// Runs `enter_high_score_name` with `name` typed so far, after `input` sets up what
+    /// the player did this frame, and returns the names in the table.
+    fn enter_name(name: &str, input: impl FnOnce(&mut World)) -> Vec<String> {
+        let mut world = World::new();
+        world.init_resource::<Events<KeyboardInput>>();
+        world.init_resource::<Events<MenuActivated>>();
+        world.init_resource::<ActionState>();
+        world.init_resource::<HighScores>();
+        world.insert_resource(HighScoreSettings { path: None });
+
+        let mut entry = HighScore::new(50, Duration::from_secs(30), 7);
+        entry.name = name.to_string();
+        world.insert_resource(PendingHighScore(entry));
+
+        input(&mut world);
+        world.run_system_once(enter_high_score_name).unwrap();
+
+        assert!(!world.contains_resource::<PendingHighScore>());
+        world
+            .resource::<HighScores>()
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect()
+    }
+
+    /// Presses an action for `enter_name`.
+    fn press(action: PlayerAction) -> impl FnOnce(&mut World) {
+        move |world| world.resource_mut::<ActionState>().press(action)
+    }
+
+    #[test]
+    fn the_confirm_button_saves_the_name() {
+        assert_eq!(
+            enter_name("", press(PlayerAction::MenuConfirm)),
+            [DEFAULT_NAME]
+        );
+        assert_eq!(enter_name("ACE", press(PlayerAction::MenuConfirm)), ["ACE"]);
+    }
+
+    #[test]
+    fn the_back_button_skips_the_name() {
+        assert_eq!(
+            enter_name("ACE", press(PlayerAction::MenuBack)),
+            [DEFAULT_NAME]
+        );
+    }
+
+    #[test]
+    fn tapping_save_saves_the_name() {
+        let tap_save = |world: &mut World| {
+            let button = world.spawn(SaveNameButton).id();
+            world.send_event(MenuActivated(button));
+        };
+
+        assert_eq!(enter_name("", tap_save), [DEFAULT_NAME]);
+    }
 }
@@ -2,11 +2,22 @@
 
 use bevy::prelude::*;
 
+use crate::ship::PlayerLives;
+
+/// Marker component for every element of the in-game UI.
+#[derive(Component)]
+pub struct GameUi;
+
 #[derive(Component)]
 pub struct ScoreText;
 
+/// Marker component for the text that shows the player's remaining lives.
+#[derive(Component)]
+pub struct LivesText;
+
 pub fn setup_game_ui(mut commands: Commands) {
     commands.spawn((
+        GameUi,
         ScoreText,
         Text::new("Score: 0"),
         TextFont {
@@ -22,9 +33,25 @@ pub fn setup_game_ui(mut commands: Commands) {
             ..default()
         },
     ));
+
+    commands.spawn((
+        GameUi,
+        LivesText,
+        Text::new("Lives: 0"),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(2.0),
+            top: Val::Percent(5.0),
+            ..default()
+        },
+    ));
 }
 
-pub fn despawn_game_ui(mut commands: Commands, query: Query<Entity, With<ScoreText>>) {
+pub fn despawn_game_ui(mut commands: Commands, query: Query<Entity, With<GameUi>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn();
     }
@@ -42,22 +69,35 @@ pub struct PlayerScore(pub i32);
 #[derive(Event)]
 pub struct ScoreEvent(pub i32);
 
-/// Updates the score text in the UI based on the player's score.
+/// Adds every `ScoreEvent` to the player's score.
 ///
 /// # Arguments
-/// * `query`: A query that retrieves the `ScoreText` component.
 /// * `events`: An event reader to read `ScoreEvent` events.
 /// * `player_score`: A mutable reference to the `PlayerScore` resource.
-pub fn update_score(
-    mut query: Query<&mut Text, With<ScoreText>>,
-    mut events: EventReader<ScoreEvent>,
-    mut player_score: ResMut<PlayerScore>,
-) {
+pub fn tally_score(mut events: EventReader<ScoreEvent>, mut player_score: ResMut<PlayerScore>) {
     for event in events.read() {
         player_score.0 += event.0;
     }
+}
+
+/// Updates the score text in the UI based on the player's score.
+///
+/// # Arguments
+/// * `query`: A query that retrieves the `ScoreText` component.
+/// * `player_score`: The `PlayerScore` resource to display.
+pub fn update_score(mut query: Query<&mut Text, With<ScoreText>>, player_score: Res<PlayerScore>) {
+    for mut text in query.iter_mut() {
+        text.0 = format!("Score: {}", player_score.0);
+    }
+}
 
+/// Updates the lives text in the UI based on the player's remaining lives.
+///
+/// # Arguments
+/// * `query`: A query that retrieves the `LivesText` component.
+/// * `lives`: The `PlayerLives` resource to display.
+pub fn update_lives(mut query: Query<&mut Text, With<LivesText>>, lives: Res<PlayerLives>) {
     for mut text in query.iter_mut() {
-        (*text).0 = format!("Score: {}", player_score.0);
+        text.0 = format!("Lives: {}", lives.remaining);
     }
 }
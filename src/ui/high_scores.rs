@@ -0,0 +1,148 @@
+//! The high score screen, with the best runs so far.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    controls::{ActionState, PlayerAction},
+    highscore::HighScores,
+    ui::{Menu, MenuActivated, MenuItem, menu_item, menu_node},
+};
+
+/// The color of the entry added this session.
+const LATEST_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// The width of each column of the table, from left to right.
+const COLUMN_WIDTHS: [f32; 6] = [50.0, 180.0, 100.0, 100.0, 150.0, 220.0];
+
+/// Marker component for the high score UI.
+#[derive(Component)]
+pub struct HighScoresUi;
+
+/// Sets up the high score screen with a row for every entry in the table.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+/// * `scores`: The `HighScores` resource to show.
+pub fn setup_high_scores_ui(mut commands: Commands, scores: Res<HighScores>) {
+    commands
+        .spawn((
+            HighScoresUi,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("High Scores"),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                Node {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+            ));
+
+            if scores.entries().is_empty() {
+                parent.spawn((
+                    Text::new("No high scores yet"),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                ));
+            } else {
+                let header = ["#", "Name", "Score", "Time", "Date", "Seed"].map(String::from);
+                spawn_row(parent, header, Color::srgb(0.6, 0.6, 0.6));
+
+                for (rank, entry) in scores.entries().iter().enumerate() {
+                    let time = entry.duration.as_secs();
+                    let cells = [
+                        format!("{}", rank + 1),
+                        entry.name.clone(),
+                        entry.score.to_string(),
+                        format!("{}:{:02}", time / 60, time % 60),
+                        entry.date().unwrap_or_else(|| "-".to_string()),
+                        entry.seed.to_string(),
+                    ];
+                    let color = if scores.latest == Some(rank) {
+                        LATEST_COLOR
+                    } else {
+                        Color::WHITE
+                    };
+
+                    spawn_row(parent, cells, color);
+                }
+            }
+
+            parent
+                .spawn((Menu::default(), menu_node()))
+                .with_child(menu_item("Back"));
+        });
+}
+
+/// Spawns a row of the high score table.
+///
+/// # Arguments
+/// * `parent`: The node to spawn the row in.
+/// * `cells`: The text of each column.
+/// * `color`: The color of the row's text.
+fn spawn_row(parent: &mut ChildSpawnerCommands, cells: [String; 6], color: Color) {
+    parent.spawn(Node::default()).with_children(|row| {
+        for (cell, width) in cells.into_iter().zip(COLUMN_WIDTHS) {
+            row.spawn((
+                Text::new(cell),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(color),
+                Node {
+                    width: Val::Px(width),
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+/// Goes back to the main menu when `Back` is picked or the back action is pressed.
+///
+/// # Arguments
+/// * `actions`: The `ActionState` resource to check for the back action.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `items`: A query that checks that a picked option is a `MenuItem`.
+/// * `next_state`: The `NextState<GameState>` resource to leave the high score screen.
+pub fn handle_high_scores_input(
+    actions: Res<ActionState>,
+    mut activated: EventReader<MenuActivated>,
+    items: Query<(), With<MenuItem>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let back = activated
+        .read()
+        .any(|MenuActivated(entity)| items.contains(*entity));
+
+    if back || actions.just_pressed(PlayerAction::MenuBack) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+/// Deletes the high score UI from the game.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the high score UI.
+/// * `query`: A query that retrieves all entities with the `HighScoresUi` component.
+pub fn despawn_high_scores_ui(mut commands: Commands, query: Query<Entity, With<HighScoresUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
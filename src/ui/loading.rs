@@ -0,0 +1,104 @@
+//! The loading screen, shown while the game's assets load.
+
+use bevy::prelude::*;
+
+use crate::audio::AudioAssets;
+
+/// Marker component for the loading screen UI.
+#[derive(Component)]
+pub struct LoadingUi;
+
+/// Marker component for the text that shows how far loading has come.
+#[derive(Component)]
+pub struct LoadingText;
+
+/// Marker component for the bar that fills up as the assets load.
+#[derive(Component)]
+pub struct LoadingBar;
+
+/// Sets up the loading screen with a progress bar.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+pub fn setup_loading_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            LoadingUi,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(20.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LoadingText,
+                Text::new("Loading..."),
+                TextFont {
+                    font_size: 30.0,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(12.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BorderColor(Color::WHITE),
+                ))
+                .with_child((
+                    LoadingBar,
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE),
+                ));
+        });
+}
+
+/// Shows how many of the sounds are done loading.
+///
+/// # Arguments
+/// * `audio_assets`: The `AudioAssets` resource being loaded.
+/// * `asset_server`: The `AssetServer` resource to check the sounds' load states.
+/// * `text`: A query that retrieves the `Text` of the `LoadingText`.
+/// * `bar`: A query that retrieves the `Node` of the `LoadingBar`.
+pub fn update_loading_ui(
+    audio_assets: Option<Res<AudioAssets>>,
+    asset_server: Res<AssetServer>,
+    mut text: Query<&mut Text, With<LoadingText>>,
+    mut bar: Query<&mut Node, With<LoadingBar>>,
+) {
+    let Some(audio_assets) = audio_assets else {
+        return;
+    };
+
+    let (done, total) = audio_assets.progress(&asset_server);
+
+    for mut text in text.iter_mut() {
+        text.0 = format!("Loading... {done}/{total}");
+    }
+    for mut node in bar.iter_mut() {
+        node.width = Val::Percent(100.0 * done as f32 / total as f32);
+    }
+}
+
+/// Deletes the loading screen UI from the game.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the loading screen UI.
+/// * `query`: A query that retrieves all entities with the `LoadingUi` component.
+pub fn despawn_loading_ui(mut commands: Commands, query: Query<Entity, With<LoadingUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
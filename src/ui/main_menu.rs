@@ -1,11 +1,26 @@
 use bevy::{prelude::*, text::LineHeight};
 
-use crate::GameState;
+use crate::{
+    GameState,
+    controls::{ActionState, PlayerAction},
+    ui::{Menu, MenuActivated, SettingsState, menu_item, menu_node},
+};
 
 /// Marker component for the main menu UI.
 #[derive(Component)]
 pub struct MainMenu;
 
+/// The options on the main menu.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainMenuAction {
+    /// Start a new run.
+    Start,
+    /// Show the high score table.
+    HighScores,
+    /// Open the settings menu.
+    Settings,
+}
+
 /// Sets up the main menu UI for the game.
 ///
 /// # Arguments
@@ -18,31 +33,60 @@ pub fn setup_main_menu(mut commands: Commands) {
                 display: Display::Flex,
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
                 ..default()
             },
         ))
-        .with_child((
-            TextLayout::new_with_justify(JustifyText::Center),
-            TextFont {
-                font_size: 40.0,
-                line_height: LineHeight::RelativeToFont(2.0),
-                ..default()
-            },
-            Text::new("Welcome to Asteroids!\nPress 'Space' to Start"),
-        ));
+        .with_children(|parent| {
+            parent.spawn((
+                TextLayout::new_with_justify(JustifyText::Center),
+                TextFont {
+                    font_size: 40.0,
+                    line_height: LineHeight::RelativeToFont(2.0),
+                    ..default()
+                },
+                Text::new("Welcome to Asteroids!"),
+            ));
+            parent
+                .spawn((Menu::default(), menu_node()))
+                .with_children(|menu| {
+                    menu.spawn((MainMenuAction::Start, menu_item("Start")));
+                    menu.spawn((MainMenuAction::HighScores, menu_item("High Scores")));
+                    menu.spawn((MainMenuAction::Settings, menu_item("Settings")));
+                });
+        });
 }
 
-/// Handles input for the main menu.
+/// Handles the options picked on the main menu. The start action starts a run right away.
+///
+/// # Arguments
+/// * `player_actions`: The `ActionState` resource to check for the start action.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `actions`: A query that retrieves the `MainMenuAction` of every option.
+/// * `next_state`: The `NextState<GameState>` resource to leave the main menu.
+/// * `next_settings_state`: The `NextState<SettingsState>` resource to open the settings menu.
 pub fn handle_main_menu_input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_actions: Res<ActionState>,
+    mut activated: EventReader<MenuActivated>,
+    actions: Query<&MainMenuAction>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        // Transition to the game state when space is pressed
+    if player_actions.just_pressed(PlayerAction::Start) {
+        // Transition to the game state when start is pressed
         next_state.set(GameState::Game);
     }
+
+    for MenuActivated(entity) in activated.read() {
+        match actions.get(*entity) {
+            Ok(MainMenuAction::Start) => next_state.set(GameState::Game),
+            Ok(MainMenuAction::HighScores) => next_state.set(GameState::HighScores),
+            Ok(MainMenuAction::Settings) => next_settings_state.set(SettingsState::Open),
+            Err(_) => {}
+        }
+    }
 }
 
 /// Deletes the main menu UI from the game.
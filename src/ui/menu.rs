@@ -0,0 +1,137 @@
+//! A list of options that can be picked with the keyboard or the mouse, shared by every menu.
+
+use bevy::prelude::*;
+
+use crate::controls::{ActionState, PlayerAction};
+
+/// The color of the selected option in a menu.
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+/// The color of the other options in a menu.
+const UNSELECTED_COLOR: Color = Color::WHITE;
+
+/// A menu, whose `MenuItem` children are its options from top to bottom.
+#[derive(Component, Debug, Default)]
+#[require(Node)]
+pub struct Menu {
+    /// The index of the selected option.
+    pub selected: usize,
+}
+
+/// An option in a `Menu`.
+///
+/// Screens add their own component to each option to tell them apart, and react to
+/// `MenuActivated` events for it.
+#[derive(Component, Debug)]
+#[require(Button)]
+pub struct MenuItem;
+
+/// Sent when the player picks an option in a menu.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MenuActivated(pub Entity);
+
+/// Returns the `Node` for a menu with its options stacked in a centred column.
+pub fn menu_node() -> Node {
+    Node {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(10.0),
+        margin: UiRect::top(Val::Px(20.0)),
+        ..default()
+    }
+}
+
+/// Returns the components of a menu option with the given label.
+///
+/// # Arguments
+/// * `label`: The text shown for the option.
+pub fn menu_item(label: impl Into<String>) -> impl Bundle {
+    (
+        MenuItem,
+        Text::new(label),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(UNSELECTED_COLOR),
+    )
+}
+
+/// Moves the selection of every menu with the menu up and down actions, and picks the
+/// selected option with the confirm action. Hovering an option with the mouse selects it,
+/// and clicking picks it.
+///
+/// # Arguments
+/// * `actions`: The `ActionState` resource to navigate with.
+/// * `menus`: A query that retrieves every `Menu` and its children.
+/// * `items`: A query that retrieves the `Interaction` of every `MenuItem`.
+/// * `activated`: The `EventWriter` to send the picked options to.
+pub fn navigate_menus(
+    actions: Res<ActionState>,
+    mut menus: Query<(&mut Menu, &Children)>,
+    items: Query<Ref<Interaction>, With<MenuItem>>,
+    mut activated: EventWriter<MenuActivated>,
+) {
+    for (mut menu, children) in menus.iter_mut() {
+        let options = children
+            .iter()
+            .filter(|child| items.contains(*child))
+            .collect::<Vec<_>>();
+
+        if options.is_empty() {
+            continue;
+        }
+
+        // The mouse takes over the selection while it moves over the options.
+        for (index, option) in options.iter().enumerate() {
+            let interaction = items.get(*option).unwrap();
+
+            if interaction.is_changed() {
+                match *interaction {
+                    Interaction::Hovered => menu.selected = index,
+                    Interaction::Pressed => {
+                        menu.selected = index;
+                        activated.write(MenuActivated(*option));
+                    }
+                    Interaction::None => {}
+                }
+            }
+        }
+
+        if actions.just_pressed(PlayerAction::MenuUp) {
+            menu.selected = (menu.selected + options.len() - 1) % options.len();
+        }
+        if actions.just_pressed(PlayerAction::MenuDown) {
+            menu.selected = (menu.selected + 1) % options.len();
+        }
+
+        menu.selected = menu.selected.min(options.len() - 1);
+
+        if actions.just_pressed(PlayerAction::MenuConfirm) {
+            activated.write(MenuActivated(options[menu.selected]));
+        }
+    }
+}
+
+/// Highlights the selected option of every menu.
+///
+/// # Arguments
+/// * `menus`: A query that retrieves every `Menu` and its children.
+/// * `items`: A query that retrieves the `TextColor` of every `MenuItem`.
+pub fn highlight_menu_items(
+    menus: Query<(&Menu, &Children)>,
+    mut items: Query<&mut TextColor, With<MenuItem>>,
+) {
+    for (menu, children) in menus.iter() {
+        let mut options = items.iter_many_mut(children);
+        let mut index = 0;
+
+        while let Some(mut color) = options.fetch_next() {
+            color.0 = if index == menu.selected {
+                SELECTED_COLOR
+            } else {
+                UNSELECTED_COLOR
+            };
+            index += 1;
+        }
+    }
+}
@@ -2,29 +2,162 @@
 //!
 //! This module contains the user interface components of the application.
 
+pub mod loading;
+pub use loading::*;
 pub mod game_ui;
 pub use game_ui::*;
 pub mod main_menu;
 pub use main_menu::*;
 pub mod game_over;
 pub use game_over::*;
+pub mod menu;
+pub use menu::*;
+pub mod high_scores;
+pub use high_scores::*;
+pub mod pause_menu;
+pub use pause_menu::*;
+pub mod settings;
+pub use settings::*;
+pub mod controls;
+pub use controls::*;
+pub mod touch;
+pub use touch::*;
 
-use crate::GameState;
+use crate::{
+    GameState, InGame,
+    audio::save_audio_settings,
+    controls::{Rebinding, save_input_map},
+    highscore::PendingHighScore,
+    pause::PauseState,
+};
 use bevy::prelude::*;
 
 pub struct GameUiPlugin;
 
 impl Plugin for GameUiPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlayerScore(0));
-        app.add_event::<ScoreEvent>();
+        app.add_systems(OnEnter(GameState::Loading), setup_loading_ui);
+        app.add_systems(
+            Update,
+            update_loading_ui.run_if(in_state(GameState::Loading)),
+        );
+        app.add_systems(OnExit(GameState::Loading), despawn_loading_ui);
+
         app.add_systems(OnEnter(GameState::MainMenu), setup_main_menu);
         app.add_systems(
             Update,
-            (handle_main_menu_input).run_if(in_state(GameState::MainMenu)),
+            (handle_main_menu_input)
+                .after(navigate_menus)
+                .run_if(in_state(GameState::MainMenu).and(in_state(SettingsState::Closed))),
         );
         app.add_systems(OnExit(GameState::MainMenu), despawn_main_menu);
-        app.add_systems(OnEnter(GameState::Game), (setup_game_ui, restart_score));
-        app.add_systems(Update, (update_score).run_if(in_state(GameState::Game)));
+        app.add_systems(OnEnter(InGame), setup_game_ui);
+        app.add_systems(
+            Update,
+            (update_score.after(tally_score), update_lives).run_if(in_state(InGame)),
+        );
+        app.add_systems(OnExit(InGame), despawn_game_ui);
+
+        // Show the on-screen controls while playing on a touch screen.
+        app.add_systems(OnEnter(GameState::Game), setup_touch_controls);
+        app.add_systems(
+            Update,
+            update_touch_controls.run_if(in_state(GameState::Game)),
+        );
+        app.add_systems(OnExit(GameState::Game), despawn_touch_controls);
+
+        // Game <-> Paused
+        app.add_systems(OnEnter(PauseState::Paused), setup_pause_menu);
+        app.add_systems(
+            Update,
+            (handle_pause_menu_input)
+                .after(navigate_menus)
+                .run_if(in_state(PauseState::Paused).and(in_state(SettingsState::Closed))),
+        );
+        app.add_systems(OnExit(PauseState::Paused), despawn_pause_menu);
+
+        // Main Menu or Paused -> Settings -> back to where it was opened from
+        app.init_state::<SettingsState>();
+        app.add_systems(
+            OnEnter(SettingsState::Open),
+            (despawn_main_menu, despawn_pause_menu, setup_settings_menu),
+        );
+        app.add_systems(
+            Update,
+            (handle_settings_menu_input, update_settings_menu)
+                .chain()
+                .after(navigate_menus)
+                .run_if(in_state(SettingsState::Open)),
+        );
+        app.add_systems(
+            OnExit(SettingsState::Open),
+            (despawn_settings_menu, save_audio_settings, save_input_map),
+        );
+        app.add_systems(
+            OnTransition {
+                exited: SettingsState::Open,
+                entered: SettingsState::Closed,
+            },
+            (
+                setup_main_menu.run_if(in_state(GameState::MainMenu)),
+                setup_pause_menu.run_if(in_state(PauseState::Paused)),
+            ),
+        );
+
+        // Settings -> Controls -> Settings
+        app.add_systems(OnEnter(SettingsState::Controls), setup_controls_menu);
+        app.add_systems(
+            Update,
+            // The key that starts or ends a rebinding is only used for that, so the
+            // rebinding starts and ends at the end of the frame.
+            (
+                capture_rebinding.run_if(resource_exists::<Rebinding>),
+                handle_controls_menu_input,
+                update_controls_menu,
+            )
+                .chain_ignore_deferred()
+                .after(navigate_menus)
+                .run_if(in_state(SettingsState::Controls)),
+        );
+        app.add_systems(
+            OnExit(SettingsState::Controls),
+            (despawn_controls_menu, save_input_map),
+        );
+
+        // Game -> Game Over -> Game or Main Menu
+        app.add_systems(OnEnter(GameState::GameOver), setup_game_over_ui);
+        app.add_systems(
+            Update,
+            (
+                handle_game_over_input.after(navigate_menus),
+                enter_high_score_name
+                    .after(navigate_menus)
+                    .run_if(resource_exists::<PendingHighScore>),
+            )
+                .run_if(in_state(GameState::GameOver)),
+        );
+        app.add_systems(OnExit(GameState::GameOver), despawn_game_over_ui);
+
+        // Main Menu or Game Over -> High Scores -> Main Menu
+        app.add_systems(OnEnter(GameState::HighScores), setup_high_scores_ui);
+        app.add_systems(
+            Update,
+            (handle_high_scores_input)
+                .after(navigate_menus)
+                .run_if(in_state(GameState::HighScores)),
+        );
+        app.add_systems(OnExit(GameState::HighScores), despawn_high_scores_ui);
+
+        // Every menu is navigated the same way.
+        app.add_event::<MenuActivated>();
+        // The keys pressed while rebinding an action are only for the new binding.
+        app.add_systems(
+            Update,
+            (
+                navigate_menus.run_if(not(resource_exists::<Rebinding>)),
+                highlight_menu_items,
+            )
+                .chain(),
+        );
     }
 }
@@ -0,0 +1,103 @@
+//! The pause menu, shown over a paused run.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    pause::PauseState,
+    restart_run,
+    ui::{Menu, MenuActivated, SettingsState, menu_item, menu_node},
+};
+
+/// Marker component for the pause menu UI.
+#[derive(Component)]
+pub struct PauseMenu;
+
+/// The options on the pause menu.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseAction {
+    /// Carry on with the run.
+    Resume,
+    /// Abandon the run and start a new one.
+    Restart,
+    /// Open the settings menu.
+    Settings,
+    /// Abandon the run and go back to the main menu.
+    QuitToMenu,
+}
+
+/// Sets up the pause menu over the run.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+pub fn setup_pause_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            PauseMenu,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Paused"),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn((Menu::default(), menu_node()))
+                .with_children(|menu| {
+                    menu.spawn((PauseAction::Resume, menu_item("Resume")));
+                    menu.spawn((PauseAction::Restart, menu_item("Restart")));
+                    menu.spawn((PauseAction::Settings, menu_item("Settings")));
+                    menu.spawn((PauseAction::QuitToMenu, menu_item("Quit to Menu")));
+                });
+        });
+}
+
+/// Handles the options picked on the pause menu.
+///
+/// # Arguments
+/// * `commands`: The commands to restart the run.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `actions`: A query that retrieves the `PauseAction` of every option.
+/// * `next_pause_state`: The `NextState<PauseState>` resource to resume the run.
+/// * `next_state`: The `NextState<GameState>` resource to quit the run.
+/// * `next_settings_state`: The `NextState<SettingsState>` resource to open the settings menu.
+pub fn handle_pause_menu_input(
+    mut commands: Commands,
+    mut activated: EventReader<MenuActivated>,
+    actions: Query<&PauseAction>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    for MenuActivated(entity) in activated.read() {
+        match actions.get(*entity) {
+            Ok(PauseAction::Resume) => next_pause_state.set(PauseState::Running),
+            Ok(PauseAction::Restart) => commands.queue(restart_run),
+            Ok(PauseAction::Settings) => next_settings_state.set(SettingsState::Open),
+            Ok(PauseAction::QuitToMenu) => next_state.set(GameState::MainMenu),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Deletes the pause menu UI from the game.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the pause menu UI.
+/// * `query`: A query that retrieves all entities with the `PauseMenu` component.
+pub fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
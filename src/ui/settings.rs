@@ -0,0 +1,239 @@
+//! The settings menu, opened from the main menu or the pause menu.
+
+use bevy::prelude::*;
+
+use crate::{
+    audio::{AudioSettings, VOLUME_STEP},
+    controls::{ActionState, ControlScheme, PlayerAction},
+    ui::{Menu, MenuActivated, menu_item, menu_node},
+};
+
+/// Whether the settings menu is open, over whichever menu it was opened from.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+    /// The controls menu is open, from the settings menu.
+    Controls,
+}
+
+impl SettingsState {
+    /// Returns `true` if the settings or controls menu is open.
+    pub fn is_open(self) -> bool {
+        self != SettingsState::Closed
+    }
+}
+
+/// Marker component for the settings menu UI.
+#[derive(Component)]
+pub struct SettingsMenu;
+
+/// The options on the settings menu.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    Mute,
+    /// Switch between the control schemes.
+    ControlScheme,
+    /// Open the controls menu.
+    Controls,
+    /// Close the settings menu.
+    Back,
+}
+
+impl SettingsItem {
+    /// Returns the label of the option for the given settings.
+    pub fn label(self, settings: &AudioSettings, scheme: ControlScheme) -> String {
+        let percent = |volume: f32| format!("{:.0}%", volume * 100.0);
+
+        match self {
+            SettingsItem::MasterVolume => format!("Master Volume: {}", percent(settings.master)),
+            SettingsItem::MusicVolume => format!("Music Volume: {}", percent(settings.music)),
+            SettingsItem::EffectsVolume => {
+                format!("Effects Volume: {}", percent(settings.effects))
+            }
+            SettingsItem::Mute => format!("Mute: {}", if settings.muted { "On" } else { "Off" }),
+            SettingsItem::ControlScheme => format!("Control Scheme: {}", scheme.label()),
+            SettingsItem::Controls => "Controls".to_string(),
+            SettingsItem::Back => "Back".to_string(),
+        }
+    }
+
+    /// Returns the volume the option changes, if it is a volume.
+    fn volume(self, settings: &mut AudioSettings) -> Option<&mut f32> {
+        match self {
+            SettingsItem::MasterVolume => Some(&mut settings.master),
+            SettingsItem::MusicVolume => Some(&mut settings.music),
+            SettingsItem::EffectsVolume => Some(&mut settings.effects),
+            SettingsItem::Mute
+            | SettingsItem::ControlScheme
+            | SettingsItem::Controls
+            | SettingsItem::Back => None,
+        }
+    }
+}
+
+/// Moves a volume by a number of steps, keeping it between 0 and 1.
+fn step_volume(volume: f32, steps: f32) -> f32 {
+    // Rounding to whole steps keeps repeated steps from drifting.
+    ((volume / VOLUME_STEP).round() + steps).clamp(0.0, 1.0 / VOLUME_STEP) * VOLUME_STEP
+}
+
+/// Sets up the settings menu.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+/// * `settings`: The `AudioSettings` resource to show.
+/// * `scheme`: The `ControlScheme` resource to show.
+pub fn setup_settings_menu(
+    mut commands: Commands,
+    settings: Res<AudioSettings>,
+    scheme: Res<ControlScheme>,
+) {
+    commands
+        .spawn((
+            SettingsMenu,
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings"),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn((Menu::default(), menu_node()))
+                .with_children(|menu| {
+                    for item in [
+                        SettingsItem::MasterVolume,
+                        SettingsItem::MusicVolume,
+                        SettingsItem::EffectsVolume,
+                        SettingsItem::Mute,
+                        SettingsItem::ControlScheme,
+                        SettingsItem::Controls,
+                        SettingsItem::Back,
+                    ] {
+                        menu.spawn((item, menu_item(item.label(&settings, *scheme))));
+                    }
+                });
+        });
+}
+
+/// Changes the settings from the settings menu.
+///
+/// `Left` and `Right` turn the selected volume down and up, or toggle mute or the control
+/// scheme. Picking a volume turns it up, wrapping around to 0, and picking mute or the
+/// control scheme toggles it. `Back` or `Escape` closes the menu.
+///
+/// # Arguments
+/// * `actions`: The `ActionState` resource to check for the adjust and back actions.
+/// * `activated`: The `EventReader` for the picked menu options.
+/// * `menus`: A query that retrieves the settings menu and its children.
+/// * `items`: A query that retrieves the `SettingsItem` of every option.
+/// * `settings`: The `AudioSettings` resource to change.
+/// * `scheme`: The `ControlScheme` resource to change.
+/// * `next_state`: The `NextState<SettingsState>` resource to close the menu, or to open
+///   the controls menu.
+pub fn handle_settings_menu_input(
+    actions: Res<ActionState>,
+    mut activated: EventReader<MenuActivated>,
+    menus: Query<(&Menu, &Children)>,
+    items: Query<&SettingsItem>,
+    mut settings: ResMut<AudioSettings>,
+    mut scheme: ResMut<ControlScheme>,
+    mut next_state: ResMut<NextState<SettingsState>>,
+) {
+    let steps = match (
+        actions.just_pressed(PlayerAction::MenuLeft),
+        actions.just_pressed(PlayerAction::MenuRight),
+    ) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    };
+
+    if steps != 0.0 {
+        let selected = menus.iter().find_map(|(menu, children)| {
+            let mut options = items.iter_many(children);
+            options.nth(menu.selected).copied()
+        });
+
+        match selected {
+            Some(SettingsItem::Mute) => settings.muted = !settings.muted,
+            Some(SettingsItem::ControlScheme) => *scheme = scheme.toggled(),
+            Some(item) => {
+                if let Some(volume) = item.volume(&mut settings) {
+                    *volume = step_volume(*volume, steps);
+                }
+            }
+            None => {}
+        }
+    }
+
+    for MenuActivated(entity) in activated.read() {
+        match items.get(*entity) {
+            Ok(SettingsItem::Mute) => settings.muted = !settings.muted,
+            Ok(SettingsItem::ControlScheme) => *scheme = scheme.toggled(),
+            Ok(SettingsItem::Controls) => next_state.set(SettingsState::Controls),
+            Ok(SettingsItem::Back) => next_state.set(SettingsState::Closed),
+            Ok(item) => {
+                if let Some(volume) = item.volume(&mut settings) {
+                    *volume = if *volume >= 1.0 {
+                        0.0
+                    } else {
+                        step_volume(*volume, 1.0)
+                    };
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    if actions.just_pressed(PlayerAction::MenuBack) {
+        next_state.set(SettingsState::Closed);
+    }
+}
+
+/// Updates the labels of the settings menu when the settings change.
+///
+/// # Arguments
+/// * `settings`: The `AudioSettings` resource to show.
+/// * `scheme`: The `ControlScheme` resource to show.
+/// * `items`: A query that retrieves the `SettingsItem` and `Text` of every option.
+pub fn update_settings_menu(
+    settings: Res<AudioSettings>,
+    scheme: Res<ControlScheme>,
+    mut items: Query<(&SettingsItem, &mut Text)>,
+) {
+    if !settings.is_changed() && !scheme.is_changed() {
+        return;
+    }
+
+    for (item, mut text) in items.iter_mut() {
+        text.0 = item.label(&settings, *scheme);
+    }
+}
+
+/// Deletes the settings menu UI from the game.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the settings menu UI.
+/// * `query`: A query that retrieves all entities with the `SettingsMenu` component.
+pub fn despawn_settings_menu(mut commands: Commands, query: Query<Entity, With<SettingsMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
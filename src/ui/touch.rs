@@ -0,0 +1,139 @@
+//! The on-screen joystick and fire button, shown once the player touches the screen.
+
+use bevy::prelude::*;
+
+use crate::controls::{TOUCH_FIRE_RADIUS, TOUCH_MARGIN, TOUCH_STICK_RADIUS, TouchControls};
+
+/// The radius of the knob in the middle of the on-screen joystick, in pixels.
+const KNOB_RADIUS: f32 = 30.0;
+/// The color of the on-screen controls.
+const CONTROL_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.25);
+/// The color of the on-screen controls while they are held.
+const HELD_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.5);
+
+/// Marker component for every element of the on-screen controls.
+#[derive(Component)]
+pub struct TouchControlsUi;
+
+/// Marker component for the knob of the on-screen joystick.
+#[derive(Component)]
+pub struct TouchStickKnob;
+
+/// Marker component for the on-screen fire button.
+#[derive(Component)]
+pub struct TouchFireButton;
+
+/// Returns the `Node` of a circle with the given radius.
+fn circle_node(radius: f32) -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        width: Val::Px(radius * 2.0),
+        height: Val::Px(radius * 2.0),
+        ..default()
+    }
+}
+
+/// Sets up the on-screen controls, hidden until the screen is touched.
+///
+/// They line up with where `TouchControls` looks for touches.
+///
+/// # Arguments
+/// * `commands`: The commands to spawn UI elements.
+/// * `touch`: The `TouchControls` resource, to show the controls if the screen was
+///   already touched.
+pub fn setup_touch_controls(mut commands: Commands, touch: Res<TouchControls>) {
+    let visibility = if touch.active {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    commands
+        .spawn((
+            TouchControlsUi,
+            Node {
+                left: Val::Px(TOUCH_MARGIN),
+                bottom: Val::Px(TOUCH_MARGIN),
+                ..circle_node(TOUCH_STICK_RADIUS)
+            },
+            BorderRadius::MAX,
+            BackgroundColor(CONTROL_COLOR),
+            visibility,
+        ))
+        .with_children(|stick| {
+            stick.spawn((
+                TouchStickKnob,
+                Node {
+                    left: Val::Px(TOUCH_STICK_RADIUS - KNOB_RADIUS),
+                    top: Val::Px(TOUCH_STICK_RADIUS - KNOB_RADIUS),
+                    ..circle_node(KNOB_RADIUS)
+                },
+                BorderRadius::MAX,
+                BackgroundColor(HELD_COLOR),
+            ));
+        });
+
+    commands.spawn((
+        TouchControlsUi,
+        TouchFireButton,
+        Node {
+            right: Val::Px(TOUCH_MARGIN),
+            bottom: Val::Px(TOUCH_MARGIN),
+            ..circle_node(TOUCH_FIRE_RADIUS)
+        },
+        BorderRadius::MAX,
+        BackgroundColor(CONTROL_COLOR),
+        visibility,
+    ));
+}
+
+/// Shows the on-screen controls once the screen is touched, and moves them with the
+/// player's touches.
+///
+/// # Arguments
+/// * `touch`: The `TouchControls` resource to show.
+/// * `controls`: A query that retrieves the `Visibility` of the on-screen controls.
+/// * `knobs`: A query that retrieves the `Node` of the joystick's knob.
+/// * `fire_buttons`: A query that retrieves the `BackgroundColor` of the fire button.
+pub fn update_touch_controls(
+    touch: Res<TouchControls>,
+    mut controls: Query<&mut Visibility, With<TouchControlsUi>>,
+    mut knobs: Query<&mut Node, With<TouchStickKnob>>,
+    mut fire_buttons: Query<&mut BackgroundColor, With<TouchFireButton>>,
+) {
+    if !touch.is_changed() {
+        return;
+    }
+
+    if touch.active {
+        for mut visibility in controls.iter_mut() {
+            *visibility = Visibility::Inherited;
+        }
+    }
+
+    // The stick's y points up, and the UI's down.
+    let knob = Vec2::new(touch.stick.x, -touch.stick.y) * TOUCH_STICK_RADIUS;
+    for mut node in knobs.iter_mut() {
+        node.left = Val::Px(TOUCH_STICK_RADIUS - KNOB_RADIUS + knob.x);
+        node.top = Val::Px(TOUCH_STICK_RADIUS - KNOB_RADIUS + knob.y);
+    }
+
+    for mut color in fire_buttons.iter_mut() {
+        color.0 = if touch.fire {
+            HELD_COLOR
+        } else {
+            CONTROL_COLOR
+        };
+    }
+}
+
+/// Deletes the on-screen controls from the game.
+///
+/// # Arguments
+/// * `commands`: The commands to despawn the on-screen controls.
+/// * `query`: A query that retrieves all entities with the `TouchControlsUi` component.
+pub fn despawn_touch_controls(mut commands: Commands, query: Query<Entity, With<TouchControlsUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,206 @@
+//! Runs the game headless, the way CI simulates it, and checks the state of the run.
+
+use asteroids::{
+    AsteroidsHeadlessPlugin, GameState, RunClock,
+    asteroid::{Asteroid, AsteroidSize, AsteroidSpawnTimer},
+    bullet::{Bullet, BulletConfig},
+    physics::Velocity,
+    rng::GameRng,
+    ship::{PlayerLives, PlayerShip},
+    stats::RunStats,
+    ui::PlayerScore,
+};
+use bevy::{
+    ecs::system::RunSystemOnce,
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+/// Creates a headless game that starts every run from the given seed.
+fn headless_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(AsteroidsHeadlessPlugin {
+        seed: Some(seed),
+        ..default()
+    });
+    app
+}
+
+/// Presses or releases a key, as the keyboard would.
+fn send_key(app: &mut App, key_code: KeyCode, logical_key: Key, state: ButtonState) {
+    app.world_mut().send_event(KeyboardInput {
+        key_code,
+        logical_key,
+        state,
+        text: None,
+        repeat: false,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+/// Plays `frames` frames, turning left the whole time and firing every 15th frame.
+fn play(app: &mut App, frames: usize) {
+    send_key(
+        app,
+        KeyCode::KeyA,
+        Key::Character("a".into()),
+        ButtonState::Pressed,
+    );
+
+    for frame in 0..frames {
+        match frame % 15 {
+            0 => send_key(app, KeyCode::Space, Key::Space, ButtonState::Pressed),
+            1 => send_key(app, KeyCode::Space, Key::Space, ButtonState::Released),
+            _ => {}
+        }
+
+        app.update();
+    }
+}
+
+/// The position and velocity of every asteroid, in a stable order.
+fn asteroids(app: &mut App) -> Vec<(Vec2, Vec2)> {
+    let mut asteroids = app
+        .world_mut()
+        .query_filtered::<(&Transform, &Velocity), With<Asteroid>>()
+        .iter(app.world())
+        .map(|(transform, velocity)| (transform.translation.truncate(), velocity.0))
+        .collect::<Vec<_>>();
+    asteroids.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.y.total_cmp(&b.0.y)));
+    asteroids
+}
+
+/// The health of the player ship, if there is one.
+fn ship_health(app: &mut App) -> Option<i32> {
+    app.world_mut()
+        .query::<&PlayerShip>()
+        .iter(app.world())
+        .next()
+        .map(|ship| ship.health)
+}
+
+#[test]
+fn starts_a_run_without_a_window() {
+    let mut app = headless_app(1);
+    app.update();
+
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::Game
+    );
+    assert_eq!(ship_health(&mut app), Some(100));
+    assert_eq!(app.world().resource::<PlayerLives>().remaining, 3);
+    assert_eq!(app.world().resource::<PlayerScore>().0, 0);
+}
+
+#[test]
+fn simulates_thousands_of_frames() {
+    let mut app = headless_app(2);
+
+    for _ in 0..3_000 {
+        app.update();
+    }
+
+    // Time only moves on by the fixed timestep, however fast the test runs.
+    let elapsed = app.world().resource::<RunClock>().0.elapsed_secs();
+    assert!((elapsed - 50.0).abs() < 0.1, "the run lasted {elapsed}s");
+
+    assert!(!asteroids(&mut app).is_empty());
+}
+
+#[test]
+fn the_same_seed_and_input_play_out_the_same() {
+    let mut first = headless_app(3);
+    let mut second = headless_app(3);
+    play(&mut first, 1_200);
+    play(&mut second, 1_200);
+
+    let first_asteroids = asteroids(&mut first);
+    assert!(!first_asteroids.is_empty());
+    assert_eq!(first_asteroids, asteroids(&mut second));
+
+    assert_eq!(ship_health(&mut first), ship_health(&mut second));
+    assert_eq!(
+        first.world().resource::<PlayerScore>().0,
+        second.world().resource::<PlayerScore>().0
+    );
+
+    let (first_stats, second_stats) = (
+        first.world().resource::<RunStats>(),
+        second.world().resource::<RunStats>(),
+    );
+    assert!(first_stats.shots_fired > 0);
+    assert_eq!(first_stats.shots_fired, second_stats.shots_fired);
+    assert_eq!(first_stats.shots_hit, second_stats.shots_hit);
+
+    // A different seed spawns different asteroids.
+    let mut other = headless_app(4);
+    play(&mut other, 1_200);
+    assert_ne!(first_asteroids, asteroids(&mut other));
+}
+
+#[test]
+fn two_bullets_on_the_same_tick_split_an_asteroid_once() {
+    let mut app = headless_app(5);
+    app.update();
+
+    // Keep the spawner from adding any other asteroids.
+    app.insert_resource(AsteroidSpawnTimer(Timer::from_seconds(
+        1_000.0,
+        TimerMode::Repeating,
+    )));
+
+    let world = app.world_mut();
+    world
+        .run_system_once(
+            |mut commands: Commands,
+             mut meshes: ResMut<Assets<Mesh>>,
+             mut materials: ResMut<Assets<ColorMaterial>>,
+             mut rng: ResMut<GameRng>,
+             bullet_config: Res<BulletConfig>| {
+                Asteroid::spawn_new(
+                    AsteroidSize::Medium,
+                    Vec3::new(200.0, 200.0, 0.0),
+                    Vec2::ZERO,
+                    &mut rng.shapes,
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                );
+
+                // Both bullets start inside the asteroid, so they hit it on the next tick.
+                for _ in 0..2 {
+                    Bullet::spawn_bullet(
+                        &mut commands,
+                        Transform::from_xyz(195.0, 200.0, 0.0),
+                        Velocity::default(),
+                        &bullet_config,
+                    );
+                }
+            },
+        )
+        .unwrap();
+
+    app.update();
+
+    let sizes = app
+        .world_mut()
+        .query::<&Asteroid>()
+        .iter(app.world())
+        .map(|asteroid| asteroid.size)
+        .collect::<Vec<_>>();
+    assert_eq!(sizes, [AsteroidSize::Small, AsteroidSize::Small]);
+
+    let bullets = app
+        .world_mut()
+        .query_filtered::<(), With<Bullet>>()
+        .iter(app.world())
+        .count();
+    assert_eq!(bullets, 1);
+
+    assert_eq!(app.world().resource::<PlayerScore>().0, 1);
+    assert_eq!(app.world().resource::<RunStats>().shots_hit, 1);
+}